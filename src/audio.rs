@@ -7,21 +7,42 @@
 // At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
 // LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
 
-#[cfg(not(test))]
-use crate::math::Libm;
+use_libm!();
 
-use crate::chan::{Ch16, Ch24, Ch32, Ch64, Channel};
-use crate::frame::Frame;
+use crate::chan::{Ch16, Ch24, Ch32, Ch32i, Ch64, Channel};
+use crate::error::{check_channels, check_u8_buffer, AudioError};
+use crate::frame::{Blend, Frame};
+use crate::sink::VecSink;
 use crate::{Sink, Stream};
 
 use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
 use alloc::slice::{Iter, IterMut};
+use alloc::string::String;
 use alloc::{vec, vec::Vec};
 
 use core::convert::TryInto;
+use core::marker::PhantomData;
 use core::num::NonZeroU32;
+use core::ptr::slice_from_raw_parts_mut;
+use core::time::Duration;
 use core::{fmt::Debug, mem::size_of, slice::from_raw_parts_mut};
 
+#[cfg(feature = "aiff")]
+mod aiff;
+mod analysis;
+#[cfg(feature = "ndarray")]
+mod array;
+#[cfg(feature = "fft")]
+mod convolve;
+mod effect;
+#[cfg(feature = "rayon")]
+mod par;
+mod reverb;
+mod stretch;
+
+pub use effect::{Expander, Side};
+
 /// Audio buffer (fixed-size array of audio [`Frame`](crate::frame::Frame)s at
 /// sample rate specified in hertz).
 #[derive(Debug)]
@@ -29,7 +50,31 @@ pub struct Audio<Chan: Channel, const CH: usize> {
     // Sample rate of the audio in hertz.
     sample_rate: NonZeroU32,
     // Audio frames.
-    frames: Box<[Frame<Chan, CH>]>,
+    frames: Vec<Frame<Chan, CH>>,
+    // Metadata tags (see `set_tag()` for which operations keep them).
+    tags: BTreeMap<String, String>,
+}
+
+/// How [`Audio::to_rate()`] changes the sample rate.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RateMode {
+    /// Resample, keeping the duration and pitch (the frame count changes).
+    Resample,
+    /// Only change the stored sample rate, keeping the frames (varispeed: the
+    /// duration and pitch change).
+    Relabel,
+}
+
+/// How well two [`Audio`] buffers match up for mixing, as returned by
+/// [`Audio::compatibility()`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Compatibility {
+    /// Same sample rate and length.
+    Compatible,
+    /// Same sample rate, but different lengths (mixing pads the shorter).
+    DifferentLength,
+    /// Different sample rates, so one needs resampling before mixing.
+    DifferentSampleRate,
 }
 
 impl<Chan: Channel, const CH: usize> Audio<Chan, CH> {
@@ -39,6 +84,22 @@ impl<Chan: Channel, const CH: usize> Audio<Chan, CH> {
         Self::with_frames(hz, vec![Frame::<Chan, CH>::default(); len])
     }
 
+    /// Construct an `Audio` buffer of silence lasting `duration` (rounded to
+    /// the nearest frame).
+    pub fn with_silence_duration(hz: u32, duration: Duration) -> Self {
+        Self::with_silence(hz, duration_len(hz, duration))
+    }
+
+    /// Construct an empty `Audio` buffer, with capacity for `duration` worth of
+    /// frames (rounded to the nearest frame).
+    pub fn with_capacity_duration(hz: u32, duration: Duration) -> Self {
+        Audio {
+            sample_rate: hz.try_into().unwrap(),
+            frames: Vec::with_capacity(duration_len(hz, duration)),
+            tags: BTreeMap::new(),
+        }
+    }
+
     /// Construct an `Audio` buffer with owned sample data.   You can get
     /// ownership of the sample data back from the `Audio` buffer as either a
     /// `Vec<S>` or a `Box<[S]>` by calling into().
@@ -49,10 +110,20 @@ impl<Chan: Channel, const CH: usize> Audio<Chan, CH> {
     {
         Audio {
             sample_rate: hz.try_into().unwrap(),
-            frames: frames.into(),
+            frames: frames.into().into(),
+            tags: BTreeMap::new(),
         }
     }
 
+    /// Construct an `Audio` buffer by collecting an iterator of frames, as
+    /// from a generator or a chain of iterator adapters.
+    pub fn from_frames_iter<I>(hz: u32, frames: I) -> Self
+    where
+        I: IntoIterator<Item = Frame<Chan, CH>>,
+    {
+        Self::with_frames(hz, frames.into_iter().collect::<Vec<_>>())
+    }
+
     /// Construct an `Audio` buffer from another `Audio` buffer of a different
     /// format.
     #[inline(always)]
@@ -69,9 +140,64 @@ impl<Chan: Channel, const CH: usize> Audio<Chan, CH> {
         let mut sink = crate::SinkTo::<_, Chan, _, CH, N>::new(output.sink());
         stream.pipe(audio, &mut sink);
         stream.flush(&mut sink);
+        output.tags = audio.tags.clone();
         output
     }
 
+    /// Convert to a different sample format (but the same sample rate and
+    /// channels) in a new buffer.
+    ///
+    /// Unlike [`with_audio()`](Self::with_audio), this never goes through the
+    /// resampler; each sample is converted directly.
+    pub fn cast<C: Channel + From<Chan>>(&self) -> Audio<C, CH> {
+        let frames = self
+            .iter()
+            .map(|frame| {
+                let mut out = Frame::<C, CH>::default();
+                for (out, chan) in
+                    out.channels_mut().iter_mut().zip(frame.channels())
+                {
+                    *out = C::from(*chan);
+                }
+                out
+            })
+            .collect::<Vec<_>>();
+        let mut audio = Audio::with_frames(self.sample_rate.get(), frames);
+        audio.tags = self.tags.clone();
+        audio
+    }
+
+    /// Convert to a different sample format, channel count and sample rate in
+    /// one call.
+    ///
+    /// Steps are ordered to do the least work: when downmixing, channels are
+    /// mixed before resampling, so fewer channels go through the resampler;
+    /// when upmixing, they are mixed after.  If the sample rate doesn't change,
+    /// the resampler is skipped and each frame is converted directly.
+    pub fn convert_to<C, const N: usize>(&self, dst_hz: u32) -> Audio<C, N>
+    where
+        C: Channel + From<Chan>,
+        Ch32: From<Chan>,
+    {
+        if dst_hz == self.sample_rate.get() {
+            let frames =
+                self.iter().map(|frame| frame.to()).collect::<Vec<_>>();
+            let mut audio = Audio::with_frames(dst_hz, frames);
+            audio.tags = self.tags.clone();
+            audio
+        } else if N < CH {
+            let frames = self
+                .iter()
+                .map(|frame| frame.to::<Chan, N>())
+                .collect::<Vec<_>>();
+            let mut mixed = Audio::with_frames(self.sample_rate.get(), frames);
+            mixed.tags = self.tags.clone();
+            Audio::with_audio(dst_hz, &mixed)
+        } else {
+            Audio::with_audio(dst_hz, self)
+        }
+    }
+
     /// Get an audio frame.
     #[inline(always)]
     pub fn get(&self, index: usize) -> Option<Frame<Chan, CH>> {
@@ -108,12 +234,93 @@ impl<Chan: Channel, const CH: usize> Audio<Chan, CH> {
         self.frames.iter_mut()
     }
 
+    /// Returns an iterator over the audio frames, each paired with its
+    /// timestamp from the start of the buffer (rounded down to the
+    /// nanosecond).
+    pub fn iter_timed(
+        &self,
+    ) -> impl Iterator<Item = (Duration, &Frame<Chan, CH>)> + '_ {
+        let hz = u64::from(self.sample_rate.get());
+        self.frames.iter().enumerate().map(move |(i, frame)| {
+            let (secs, rem) = (i as u64 / hz, i as u64 % hz);
+            let nanos = (rem * 1_000_000_000 / hz) as u32;
+            (Duration::new(secs, nanos), frame)
+        })
+    }
+
+    /// Transform each audio frame.
+    pub fn map<F>(&mut self, mut f: F)
+    where
+        F: FnMut(Frame<Chan, CH>) -> Frame<Chan, CH>,
+    {
+        for frame in self.frames.iter_mut() {
+            *frame = f(*frame);
+        }
+    }
+
+    /// Returns an iterator over the audio frames, with each channel converted
+    /// to `f32`.
+    pub fn frames_f32(&self) -> impl Iterator<Item = [f32; CH]> + '_ {
+        self.frames.iter().map(|frame| {
+            let mut samples = [0.0; CH];
+            for (sample, chan) in samples.iter_mut().zip(frame.channels()) {
+                *sample = chan.to_f32();
+            }
+            samples
+        })
+    }
+
     /// Get the sample rate of this audio buffer.
     #[inline(always)]
     pub fn sample_rate(&self) -> NonZeroU32 {
         self.sample_rate
     }
 
+    /// Check if `other` has the same sample rate, so the two can be mixed.
+    #[inline(always)]
+    pub fn is_compatible_with(&self, other: &Self) -> bool {
+        self.sample_rate == other.sample_rate
+    }
+
+    /// Check how well `other` matches this buffer for mixing.  A sample rate
+    /// mismatch is reported over a length mismatch.
+    pub fn compatibility(&self, other: &Self) -> Compatibility {
+        if !self.is_compatible_with(other) {
+            Compatibility::DifferentSampleRate
+        } else if self.len() != other.len() {
+            Compatibility::DifferentLength
+        } else {
+            Compatibility::Compatible
+        }
+    }
+
+    /// Set a metadata tag (such as a title, source device or take number),
+    /// replacing any previous value for `key`.
+    ///
+    /// Tags are carried along with the buffer, but aren't used by any
+    /// processing.  They're kept by [`split_at()`](Self::split_at) and in the
+    /// buffers made by [`with_audio()`](Self::with_audio),
+    /// [`to_rate()`](Self::to_rate), [`cast()`](Self::cast),
+    /// [`convert_to()`](Self::convert_to) and [`stretch()`](Self::stretch);
+    /// other buffers built from this one start out without tags.
+    pub fn set_tag<K, V>(&mut self, key: K, value: V)
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.tags.insert(key.into(), value.into());
+    }
+
+    /// Get the value of a metadata tag.
+    pub fn get_tag(&self, key: &str) -> Option<&str> {
+        self.tags.get(key).map(String::as_str)
+    }
+
+    /// Get all of the metadata tags.
+    pub fn tags(&self) -> &BTreeMap<String, String> {
+        &self.tags
+    }
+
     /// Get the length of the `Audio` buffer.
     #[inline(always)]
     pub fn len(&self) -> usize {
@@ -126,6 +333,41 @@ impl<Chan: Channel, const CH: usize> Audio<Chan, CH> {
         self.len() == 0
     }
 
+    /// Shorten the buffer to `len` frames, keeping the allocation.  Does
+    /// nothing if the buffer is already shorter.
+    #[inline(always)]
+    pub fn truncate(&mut self, len: usize) {
+        self.frames.truncate(len);
+    }
+
+    /// Divide the buffer in two at `mid`: the first has frames `[0, mid)`,
+    /// and the second `[mid, len)`.  Both keep the sample rate and tags.
+    ///
+    /// # Panics
+    /// If `mid > len`.
+    pub fn split_at(self, mid: usize) -> (Self, Self) {
+        assert!(mid <= self.len(), "split index {} is out of bounds", mid);
+        let mut frames = self.frames;
+        let tail = frames.split_off(mid);
+        let head = Audio {
+            sample_rate: self.sample_rate,
+            frames,
+            tags: self.tags.clone(),
+        };
+        let tail = Audio {
+            sample_rate: self.sample_rate,
+            frames: tail,
+            tags: self.tags,
+        };
+        (head, tail)
+    }
+
+    /// Remove all frames from the buffer, keeping the allocation.
+    #[inline(always)]
+    pub fn clear(&mut self) {
+        self.frames.clear();
+    }
+
     /// Silence the audio buffer.
     #[inline(always)]
     pub fn silence(&mut self) {
@@ -134,6 +376,62 @@ impl<Chan: Channel, const CH: usize> Audio<Chan, CH> {
         }
     }
 
+    /// Replace the frames starting at `at` with the frames of `src`, as for
+    /// punch-in recording or splicing.  Frames of `src` that would go past
+    /// the end of this buffer are dropped.
+    ///
+    /// # Panics
+    /// If the sample rates don't match.
+    pub fn overwrite(&mut self, at: usize, src: &Self) {
+        assert_eq!(self.sample_rate(), src.sample_rate());
+        let start = at.min(self.len());
+        for (dst, frame) in self.frames[start..].iter_mut().zip(src.iter()) {
+            *dst = *frame;
+        }
+    }
+
+    /// Blend the frames of `other` onto this buffer from the start with
+    /// `op`, as when compositing layers.  Frames of `other` past the end of
+    /// this buffer are dropped.
+    ///
+    /// # Panics
+    /// If the sample rates don't match.
+    pub fn blend(&mut self, other: &Self, op: Blend) {
+        assert_eq!(self.sample_rate(), other.sample_rate());
+        for (dst, frame) in self.frames.iter_mut().zip(other.iter()) {
+            *dst = dst.blend(*frame, op);
+        }
+    }
+
+    /// Append `frames` frames of silence, as for a gap between clips.
+    pub fn push_silence(&mut self, frames: usize) {
+        let len = self.len() + frames;
+        self.frames.resize(len, Frame::default());
+    }
+
+    /// Append `duration` worth of silence (rounded to the nearest frame).
+    pub fn push_silence_duration(&mut self, duration: Duration) {
+        self.push_silence(duration_len(self.sample_rate.get(), duration));
+    }
+
+    /// Get the length that [`pad_to_pow2()`](Self::pad_to_pow2) pads to: the
+    /// smallest power of two greater than or equal to the length, or 0 if the
+    /// buffer is empty.
+    pub fn next_pow2_len(&self) -> usize {
+        if self.is_empty() {
+            0
+        } else {
+            self.len().next_power_of_two()
+        }
+    }
+
+    /// Append silence until the length is a power of two (for radix-2 FFTs).
+    /// Empty buffers are left empty.
+    pub fn pad_to_pow2(&mut self) {
+        let len = self.next_pow2_len();
+        self.frames.resize(len, Frame::default());
+    }
+
     /// Sink audio into this audio buffer from a `Stream`.
     #[inline(always)]
     pub fn sink(&mut self) -> AudioSink<'_, Chan, CH> {
@@ -142,6 +440,266 @@ impl<Chan: Channel, const CH: usize> Audio<Chan, CH> {
             audio: self,
         }
     }
+
+    /// Resample `src` through `stream`, and append the result to the end of
+    /// this audio buffer.
+    ///
+    /// Reusing the same `stream` across calls carries the resampler state
+    /// over, so blocks of audio are joined without gaps.  The last few frames
+    /// stay buffered in the `stream` until it's flushed.
+    ///
+    /// # Panics
+    /// If the `stream`'s target sample rate doesn't match this buffer's.
+    pub fn append_resampled(&mut self, src: &Self, stream: &mut Stream<CH>)
+    where
+        Ch32: From<Chan>,
+    {
+        // Upper bound on the number of frames the resampler can produce.
+        let room = (src.len() as u64 * u64::from(self.sample_rate.get()))
+            / u64::from(src.sample_rate().get())
+            + 2;
+        let sink =
+            VecSink::new(self.sample_rate, room as usize, &mut self.frames);
+        stream.pipe(src, sink);
+    }
+
+    /// Change the sample rate to `new_hz` in a new buffer, either by
+    /// resampling or by relabeling the frames (see [`RateMode`]).
+    pub fn to_rate(&self, new_hz: u32, mode: RateMode) -> Self
+    where
+        Ch32: From<Chan>,
+    {
+        match mode {
+            RateMode::Resample => Self::with_audio(new_hz, self),
+            RateMode::Relabel => {
+                let mut audio = Self::with_frames(new_hz, self.as_slice());
+                audio.tags = self.tags.clone();
+                audio
+            }
+        }
+    }
+
+    /// Sum all channels to mono, for checking how the mix holds up when
+    /// played back in mono.
+    ///
+    /// Channels are summed at -3 dB per doubling (each scaled by 1/√n for `n`
+    /// summed channels), so uncorrelated content keeps its loudness while
+    /// in-phase content rises by 3 dB, as it does on a mono speaker.
+    /// Out-of-phase content cancels, which is what this is for catching.
+    /// The LFE channel is left out.
+    ///
+    /// In-phase content near full scale can sum past it (up to √n times),
+    /// which clips for integer channels; to keep the peaks, convert to `Ch32`
+    /// first with [`cast()`](Self::cast).
+    pub fn to_mono_compat(&self) -> Audio<Chan, 1> {
+        // Surround layouts with 6 or more channels have LFE at index 3.
+        let (lfe, summed) = if CH >= 6 {
+            (Some(3), CH - 1)
+        } else {
+            (None, CH)
+        };
+        let gain = 1.0 / (summed as f32).sqrt();
+        let frames = self
+            .iter()
+            .map(|frame| {
+                let sum = frame
+                    .channels()
+                    .iter()
+                    .enumerate()
+                    .filter(|(chan, _)| Some(*chan) != lfe)
+                    .map(|(_, x)| x.to_f32())
+                    .sum::<f32>();
+                Frame::<Chan, 1>::new(Chan::from(sum * gain))
+            })
+            .collect::<Vec<_>>();
+        Audio::with_frames(self.sample_rate.get(), frames)
+    }
+}
+
+impl<Chan: Channel> Audio<Chan, 1> {
+    /// Copy the mono signal into every channel of a new `N`-channel buffer.
+    ///
+    /// Unlike converting with [`with_audio()`](Audio::with_audio), which
+    /// places the mono signal spatially, this is a literal copy.
+    pub fn broadcast<const N: usize>(&self) -> Audio<Chan, N> {
+        let frames = self
+            .iter()
+            .map(|frame| {
+                let mut out = Frame::<Chan, N>::default();
+                for chan in out.channels_mut() {
+                    *chan = frame.channels()[0];
+                }
+                out
+            })
+            .collect::<Vec<_>>();
+        Audio::with_frames(self.sample_rate.get(), frames)
+    }
+
+    /// Join separate left and right mono buffers into a stereo buffer.
+    ///
+    /// # Panics
+    /// If the sample rates or lengths of `left` and `right` don't match.
+    pub fn join(left: &Self, right: &Self) -> Audio<Chan, 2> {
+        assert_eq!(left.sample_rate(), right.sample_rate());
+        assert_eq!(left.len(), right.len());
+        let frames = left
+            .iter()
+            .zip(right.iter())
+            .map(|(l, r)| {
+                Frame::<Chan, 2>::new(l.channels()[0], r.channels()[0])
+            })
+            .collect::<Vec<_>>();
+        Audio::with_frames(left.sample_rate.get(), frames)
+    }
+}
+
+impl<Chan: Channel> Audio<Chan, 2> {
+    /// Split a stereo buffer into separate left and right mono buffers.
+    pub fn split(&self) -> (Audio<Chan, 1>, Audio<Chan, 1>) {
+        let hz = self.sample_rate.get();
+        let channel = |chan: usize| {
+            let frames = self
+                .iter()
+                .map(|frame| Frame::<Chan, 1>::new(frame.channels()[chan]))
+                .collect::<Vec<_>>();
+            Audio::with_frames(hz, frames)
+        };
+        (channel(0), channel(1))
+    }
+}
+
+/// Get the number of frames at `hz` lasting `duration`, rounded to the nearest
+/// frame.
+fn duration_len(hz: u32, duration: Duration) -> usize {
+    let nanos = duration.as_nanos() * u128::from(hz);
+    ((nanos + 500_000_000) / 1_000_000_000) as usize
+}
+
+/// Infer the sample rate (rounded to the nearest hertz) at which
+/// `frame_count` frames last `duration`, for loading headerless PCM of known
+/// length.
+///
+/// # Panics
+/// If `duration` is zero.
+pub fn infer_sample_rate(frame_count: usize, duration: Duration) -> u32 {
+    let nanos = duration.as_nanos();
+    assert_ne!(nanos, 0);
+    let frames = frame_count as u128 * 1_000_000_000;
+    ((frames + nanos / 2) / nanos) as u32
+}
+
+/// Guess the channel count of headerless PCM that is `byte_len` bytes long,
+/// with `bit_depth` bits per sample (rounded up to whole bytes).
+///
+/// Returns the first of the `possible` channel counts that splits the buffer
+/// into whole frames, or `None` if none do.  This is only a heuristic: a
+/// buffer can often be split more than one way, so list the likeliest
+/// channel counts first.
+pub fn guess_channels(
+    byte_len: usize,
+    bit_depth: u32,
+    possible: &[usize],
+) -> Option<usize> {
+    let sample_len = (bit_depth as usize + 7) / 8;
+    possible.iter().copied().find(|&channels| {
+        let frame_len = channels * sample_len;
+        frame_len != 0 && byte_len % frame_len == 0
+    })
+}
+
+/// Get how long `frames` frames last at `hz`, in milliseconds (for buffer
+/// size and latency math).
+pub fn frames_to_ms(frames: usize, hz: u32) -> f32 {
+    (frames as f64 * 1000.0 / f64::from(hz)) as f32
+}
+
+/// Get the number of frames at `hz` lasting `ms` milliseconds, rounded to
+/// the nearest frame.
+pub fn ms_to_frames(ms: f32, hz: u32) -> usize {
+    (f64::from(ms) * f64::from(hz) / 1000.0).round() as usize
+}
+
+/// Reusable converter between audio formats.
+///
+/// Converting repeatedly with the same `Converter` reuses its output buffer,
+/// which is only reallocated when the input grows longer than it has been.
+#[derive(Debug)]
+pub struct Converter<Src: Channel, Dst: Channel, const CH: usize> {
+    output: Audio<Dst, CH>,
+    _phantom: PhantomData<Src>,
+}
+
+impl<Src, Dst, const CH: usize> Default for Converter<Src, Dst, CH>
+where
+    Src: Channel,
+    Dst: Channel + From<Src>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Src, Dst, const CH: usize> Converter<Src, Dst, CH>
+where
+    Src: Channel,
+    Dst: Channel + From<Src>,
+{
+    /// Create a new converter, with no output buffer allocated yet.
+    pub fn new() -> Self {
+        Self {
+            output: Audio {
+                sample_rate: NonZeroU32::new(1).unwrap(),
+                frames: Vec::new(),
+                tags: BTreeMap::new(),
+            },
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Convert `audio`, returning the converted audio in the reused buffer.
+    pub fn convert(&mut self, audio: &Audio<Src, CH>) -> &Audio<Dst, CH> {
+        self.output.sample_rate = audio.sample_rate;
+        self.output.frames.clear();
+        self.output
+            .frames
+            .extend(audio.iter().map(|frame| frame.to()));
+        &self.output
+    }
+}
+
+/// A mono audio buffer of any channel format.
+///
+/// This is implemented for all mono [`Audio`] buffers, so that buffers with
+/// different sample formats can be combined with
+/// [`Audio::interleave_mixed()`](crate::Audio::interleave_mixed).
+pub trait MonoBuffer: Debug {
+    /// Get the sample rate of the buffer in hertz.
+    fn sample_rate(&self) -> NonZeroU32;
+
+    /// Get the length of the buffer in frames.
+    fn len(&self) -> usize;
+
+    /// Get the sample at `index` as a 32-bit float channel.
+    fn sample(&self, index: usize) -> Ch32;
+
+    /// Check if the buffer is empty (length of zero).
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<Chan: Channel> MonoBuffer for Audio<Chan, 1> {
+    fn sample_rate(&self) -> NonZeroU32 {
+        self.sample_rate
+    }
+
+    fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    fn sample(&self, index: usize) -> Ch32 {
+        self.frames[index].channels()[0].into()
+    }
 }
 
 /// Returned from [`Audio::sink()`](crate::Audio::sink).
@@ -201,15 +759,20 @@ impl<Chan: Channel, const CH: usize> Sink<Chan, CH>
 
 impl<const CH: usize> Audio<Ch16, CH> {
     /// Construct an `Audio` buffer from an `i16` buffer.
+    ///
+    /// # Panics
+    /// If the length of `buffer` isn't a multiple of the channel count.
     #[allow(unsafe_code)]
     pub fn with_i16_buffer<B>(hz: u32, buffer: B) -> Self
     where
         B: Into<Box<[i16]>>,
     {
         let buffer: Box<[i16]> = buffer.into();
+        if let Err(error) = check_channels(buffer.len(), CH) {
+            panic!("{}", error);
+        }
         let bytes = buffer.len() * size_of::<i16>();
         let len = bytes / size_of::<Frame<Ch16, CH>>();
-        assert_eq!(0, bytes % size_of::<Frame<Ch16, CH>>());
         let slice = Box::<[i16]>::into_raw(buffer);
         let frames: Box<[Frame<Ch16, CH>]> = unsafe {
             let ptr = (*slice).as_mut_ptr() as *mut Frame<Ch16, CH>;
@@ -219,6 +782,77 @@ impl<const CH: usize> Audio<Ch16, CH> {
         Audio::with_frames(hz, frames)
     }
 
+    /// Construct an `Audio` buffer from an `i16` buffer, failing if the length
+    /// of `buffer` isn't a multiple of the channel count.
+    pub fn try_with_i16_buffer<B>(
+        hz: u32,
+        buffer: B,
+    ) -> Result<Self, AudioError>
+    where
+        B: Into<Box<[i16]>>,
+    {
+        let buffer: Box<[i16]> = buffer.into();
+        check_channels(buffer.len(), CH)?;
+        Ok(Self::with_i16_buffer(hz, buffer))
+    }
+
+    /// Construct an `Audio` buffer from as many whole frames of an interleaved
+    /// device buffer as possible, returning the leftover samples of a partial
+    /// trailing frame.
+    ///
+    /// Unlike [`with_i16_buffer()`](Self::with_i16_buffer), this doesn't
+    /// panic on a partial frame; the leftover samples can be carried over and
+    /// prepended to the next device buffer.
+    pub fn from_device_buffer_i16(hz: u32, buffer: &[i16]) -> (Self, &[i16]) {
+        let chunks = buffer.chunks_exact(CH);
+        let leftover = chunks.remainder();
+        let frames = chunks
+            .map(|samples| {
+                let mut frame = Frame::<Ch16, CH>::default();
+                for (chan, sample) in
+                    frame.channels_mut().iter_mut().zip(samples)
+                {
+                    *chan = Ch16::new(*sample);
+                }
+                frame
+            })
+            .collect::<Vec<_>>();
+        (Audio::with_frames(hz, frames), leftover)
+    }
+
+    /// Construct an `Audio` buffer from interleaved offset-binary samples
+    /// (unsigned, centered on `0x8000`), as used by some DACs and ADCs.
+    ///
+    /// # Panics
+    /// If the length of `buffer` isn't a multiple of the channel count.
+    pub fn from_offset_binary(hz: u32, buffer: &[u16]) -> Self {
+        if let Err(error) = check_channels(buffer.len(), CH) {
+            panic!("{}", error);
+        }
+        let frames = buffer
+            .chunks_exact(CH)
+            .map(|samples| {
+                let mut frame = Frame::<Ch16, CH>::default();
+                for (chan, sample) in
+                    frame.channels_mut().iter_mut().zip(samples)
+                {
+                    *chan = Ch16::new((*sample ^ 0x8000) as i16);
+                }
+                frame
+            })
+            .collect::<Vec<_>>();
+        Audio::with_frames(hz, frames)
+    }
+
+    /// Get the interleaved samples in offset-binary (unsigned, centered on
+    /// `0x8000`), by flipping the sign bit of each sample.
+    pub fn to_offset_binary(&self) -> Vec<u16> {
+        self.iter()
+            .flat_map(|frame| frame.channels().iter())
+            .map(|chan| i16::from(*chan) as u16 ^ 0x8000)
+            .collect()
+    }
+
     /// Get view of samples as an `i16` slice.
     #[allow(unsafe_code)]
     pub fn as_i16_slice(&mut self) -> &mut [i16] {
@@ -233,16 +867,21 @@ impl<const CH: usize> Audio<Ch16, CH> {
 }
 
 impl<const CH: usize> Audio<Ch24, CH> {
-    /// Construct an `Audio` buffer from an `u8` buffer.
+    /// Construct an `Audio` buffer from an `u8` buffer of interleaved 3-byte
+    /// samples.
+    ///
+    /// # Panics
+    /// If `buffer` doesn't contain whole frames of 3-byte samples.
     #[allow(unsafe_code)]
     pub fn with_u8_buffer<B>(hz: u32, buffer: B) -> Self
     where
         B: Into<Box<[u8]>>,
     {
         let buffer: Box<[u8]> = buffer.into();
-        let bytes = buffer.len() * size_of::<i16>();
-        let len = bytes / size_of::<Frame<Ch16, CH>>();
-        assert_eq!(0, bytes % size_of::<Frame<Ch16, CH>>());
+        if let Err(error) = check_u8_buffer(buffer.len(), CH) {
+            panic!("{}", error);
+        }
+        let len = buffer.len() / size_of::<Frame<Ch24, CH>>();
         let slice = Box::<[u8]>::into_raw(buffer);
         let frames: Box<[Frame<Ch24, CH>]> = unsafe {
             let ptr = (*slice).as_mut_ptr() as *mut Frame<Ch24, CH>;
@@ -252,7 +891,21 @@ impl<const CH: usize> Audio<Ch24, CH> {
         Audio::with_frames(hz, frames)
     }
 
+    /// Construct an `Audio` buffer from an `u8` buffer of interleaved 3-byte
+    /// samples, failing if `buffer` doesn't contain whole frames.
+    pub fn try_with_u8_buffer<B>(hz: u32, buffer: B) -> Result<Self, AudioError>
+    where
+        B: Into<Box<[u8]>>,
+    {
+        let buffer: Box<[u8]> = buffer.into();
+        check_u8_buffer(buffer.len(), CH)?;
+        Ok(Self::with_u8_buffer(hz, buffer))
+    }
+
     /// Get view of samples as an `u8` slice.
+    ///
+    /// Each sample is 3 bytes; use
+    /// [`copy_to_i32_slice()`](Self::copy_to_i32_slice) to get `i32` samples.
     #[allow(unsafe_code)]
     pub fn as_u8_slice(&mut self) -> &mut [u8] {
         let frames = self.as_mut_slice();
@@ -263,19 +916,113 @@ impl<const CH: usize> Audio<Ch24, CH> {
             v
         }
     }
+
+    /// Construct an `Audio` buffer from interleaved 24-bit samples stored in
+    /// (sign-extended) `i32`s.  Out of range samples are clamped.
+    ///
+    /// # Panics
+    /// If the length of `buffer` isn't a multiple of the channel count.
+    pub fn from_i32_buffer(hz: u32, buffer: &[i32]) -> Self {
+        if let Err(error) = check_channels(buffer.len(), CH) {
+            panic!("{}", error);
+        }
+        let frames = buffer
+            .chunks_exact(CH)
+            .map(|samples| {
+                let mut frame = Frame::<Ch24, CH>::default();
+                for (chan, sample) in
+                    frame.channels_mut().iter_mut().zip(samples)
+                {
+                    *chan = Ch24::new(*sample);
+                }
+                frame
+            })
+            .collect::<Vec<_>>();
+        Audio::with_frames(hz, frames)
+    }
+
+    /// Construct an `Audio` buffer from interleaved 24-bit samples stored in
+    /// (sign-extended) `i32`s, failing if the length of `buffer` isn't a
+    /// multiple of the channel count.
+    pub fn try_from_i32_buffer(
+        hz: u32,
+        buffer: &[i32],
+    ) -> Result<Self, AudioError> {
+        check_channels(buffer.len(), CH)?;
+        Ok(Self::from_i32_buffer(hz, buffer))
+    }
+
+    /// Copy the samples into an interleaved buffer of sign-extended `i32`s.
+    pub fn to_i32_buffer(&self) -> Vec<i32> {
+        let mut buffer = vec![0; self.len() * CH];
+        self.copy_to_i32_slice(&mut buffer);
+        buffer
+    }
+
+    /// Copy the samples into an interleaved slice of sign-extended `i32`s.
+    ///
+    /// Samples are packed 3-byte values (with an alignment of 1), so there is
+    /// no zero-copy `i32` view of them; transmuting the
+    /// [`as_u8_slice()`](Self::as_u8_slice) bytes to `i32` is incorrect.
+    ///
+    /// # Panics
+    /// If the length of `out` isn't the number of samples (`len() * CH`).
+    pub fn copy_to_i32_slice(&self, out: &mut [i32]) {
+        assert_eq!(self.len() * CH, out.len());
+        let samples = self.iter().flat_map(|frame| frame.channels().iter());
+        for (out, chan) in out.iter_mut().zip(samples) {
+            *out = i32::from(*chan);
+        }
+    }
 }
 
 impl<const CH: usize> Audio<Ch32, CH> {
+    /// Interleave mono buffers of (possibly) different sample formats into a
+    /// single 32-bit float buffer, one buffer per channel.
+    ///
+    /// Since every channel of a [`Frame`] shares the same format, audio with
+    /// channels stored at different bit depths (for example, a 24-bit LFE
+    /// alongside 16-bit mains) should be kept as separate mono buffers, and
+    /// combined with this function when it needs to be processed together.
+    ///
+    /// # Panics
+    /// If the buffers don't all have the same sample rate and length.
+    pub fn interleave_mixed(channels: [&dyn MonoBuffer; CH]) -> Self {
+        let hz = channels[0].sample_rate();
+        let len = channels[0].len();
+        for channel in channels.iter() {
+            assert_eq!(channel.sample_rate(), hz);
+            assert_eq!(channel.len(), len);
+        }
+        let frames = (0..len)
+            .map(|i| {
+                let mut frame = Frame::<Ch32, CH>::default();
+                for (chan, channel) in
+                    frame.channels_mut().iter_mut().zip(channels.iter())
+                {
+                    *chan = channel.sample(i);
+                }
+                frame
+            })
+            .collect::<Vec<_>>();
+        Audio::with_frames(hz.get(), frames)
+    }
+
     /// Construct an `Audio` buffer from an `f32` buffer.
+    ///
+    /// # Panics
+    /// If the length of `buffer` isn't a multiple of the channel count.
     #[allow(unsafe_code)]
     pub fn with_f32_buffer<B>(hz: u32, buffer: B) -> Self
     where
         B: Into<Box<[f32]>>,
     {
         let buffer: Box<[f32]> = buffer.into();
+        if let Err(error) = check_channels(buffer.len(), CH) {
+            panic!("{}", error);
+        }
         let bytes = buffer.len() * size_of::<f32>();
         let len = bytes / size_of::<Frame<Ch32, CH>>();
-        assert_eq!(0, bytes % size_of::<Frame<Ch32, CH>>());
         let slice = Box::<[f32]>::into_raw(buffer);
         let frames: Box<[Frame<Ch32, CH>]> = unsafe {
             let ptr = (*slice).as_mut_ptr() as *mut Frame<Ch32, CH>;
@@ -285,6 +1032,20 @@ impl<const CH: usize> Audio<Ch32, CH> {
         Audio::with_frames(hz, frames)
     }
 
+    /// Construct an `Audio` buffer from an `f32` buffer, failing if the length
+    /// of `buffer` isn't a multiple of the channel count.
+    pub fn try_with_f32_buffer<B>(
+        hz: u32,
+        buffer: B,
+    ) -> Result<Self, AudioError>
+    where
+        B: Into<Box<[f32]>>,
+    {
+        let buffer: Box<[f32]> = buffer.into();
+        check_channels(buffer.len(), CH)?;
+        Ok(Self::with_f32_buffer(hz, buffer))
+    }
+
     /// Get view of samples as an `f32` slice.
     #[allow(unsafe_code)]
     pub fn as_f32_slice(&mut self) -> &mut [f32] {
@@ -296,19 +1057,36 @@ impl<const CH: usize> Audio<Ch32, CH> {
             v
         }
     }
+
+    /// Convert to interleaved, packed 24-bit little endian samples (3 bytes
+    /// per sample), as stored in 24-bit WAV files.  Out of range samples are
+    /// clamped.
+    pub fn to_packed_24le(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.len() * CH * 3);
+        for chan in self.iter().flat_map(|frame| frame.channels().iter()) {
+            let sample = i32::from(Ch24::from(*chan));
+            bytes.extend_from_slice(&sample.to_le_bytes()[..3]);
+        }
+        bytes
+    }
 }
 
 impl<const CH: usize> Audio<Ch64, CH> {
     /// Construct an `Audio` buffer from an `f64` buffer.
+    ///
+    /// # Panics
+    /// If the length of `buffer` isn't a multiple of the channel count.
     #[allow(unsafe_code)]
     pub fn with_f64_buffer<B>(hz: u32, buffer: B) -> Self
     where
         B: Into<Box<[f64]>>,
     {
         let buffer: Box<[f64]> = buffer.into();
+        if let Err(error) = check_channels(buffer.len(), CH) {
+            panic!("{}", error);
+        }
         let bytes = buffer.len() * size_of::<f64>();
         let len = bytes / size_of::<Frame<Ch64, CH>>();
-        assert_eq!(0, bytes % size_of::<Frame<Ch64, CH>>());
         let slice = Box::<[f64]>::into_raw(buffer);
         let frames: Box<[Frame<Ch64, CH>]> = unsafe {
             let ptr = (*slice).as_mut_ptr() as *mut Frame<Ch64, CH>;
@@ -318,6 +1096,20 @@ impl<const CH: usize> Audio<Ch64, CH> {
         Audio::with_frames(hz, frames)
     }
 
+    /// Construct an `Audio` buffer from an `f64` buffer, failing if the length
+    /// of `buffer` isn't a multiple of the channel count.
+    pub fn try_with_f64_buffer<B>(
+        hz: u32,
+        buffer: B,
+    ) -> Result<Self, AudioError>
+    where
+        B: Into<Box<[f64]>>,
+    {
+        let buffer: Box<[f64]> = buffer.into();
+        check_channels(buffer.len(), CH)?;
+        Ok(Self::with_f64_buffer(hz, buffer))
+    }
+
     /// Get view of samples as an `f64` slice.
     #[allow(unsafe_code)]
     pub fn as_f64_slice(&mut self) -> &mut [f64] {
@@ -331,13 +1123,65 @@ impl<const CH: usize> Audio<Ch64, CH> {
     }
 }
 
+impl<const CH: usize> Audio<Ch32i, CH> {
+    /// Construct an `Audio` buffer from an `i32` buffer.
+    ///
+    /// # Panics
+    /// If the length of `buffer` isn't a multiple of the channel count.
+    #[allow(unsafe_code)]
+    pub fn with_i32_buffer<B>(hz: u32, buffer: B) -> Self
+    where
+        B: Into<Box<[i32]>>,
+    {
+        let buffer: Box<[i32]> = buffer.into();
+        if let Err(error) = check_channels(buffer.len(), CH) {
+            panic!("{}", error);
+        }
+        let bytes = buffer.len() * size_of::<i32>();
+        let len = bytes / size_of::<Frame<Ch32i, CH>>();
+        let slice = Box::<[i32]>::into_raw(buffer);
+        let frames: Box<[Frame<Ch32i, CH>]> = unsafe {
+            let ptr = (*slice).as_mut_ptr() as *mut Frame<Ch32i, CH>;
+            Box::from_raw(slice_from_raw_parts_mut(ptr, len))
+        };
+        let frames: Vec<Frame<Ch32i, CH>> = frames.into();
+        Audio::with_frames(hz, frames)
+    }
+
+    /// Construct an `Audio` buffer from an `i32` buffer, failing if the length
+    /// of `buffer` isn't a multiple of the channel count.
+    pub fn try_with_i32_buffer<B>(
+        hz: u32,
+        buffer: B,
+    ) -> Result<Self, AudioError>
+    where
+        B: Into<Box<[i32]>>,
+    {
+        let buffer: Box<[i32]> = buffer.into();
+        check_channels(buffer.len(), CH)?;
+        Ok(Self::with_i32_buffer(hz, buffer))
+    }
+
+    /// Get view of samples as an `i32` slice.
+    #[allow(unsafe_code)]
+    pub fn as_i32_slice(&mut self) -> &mut [i32] {
+        let frames = self.as_mut_slice();
+        unsafe {
+            let (prefix, v, suffix) = frames.align_to_mut::<i32>();
+            debug_assert!(prefix.is_empty());
+            debug_assert!(suffix.is_empty());
+            v
+        }
+    }
+}
+
 impl<Chan, const CH: usize> From<Audio<Chan, CH>> for Vec<Frame<Chan, CH>>
 where
     Chan: Channel,
 {
     /// Get internal sample data as `Vec` of audio frames.
     fn from(audio: Audio<Chan, CH>) -> Self {
-        audio.frames.into()
+        audio.frames
     }
 }
 
@@ -346,8 +1190,7 @@ impl<Chan: Channel, const CH: usize> From<Audio<Chan, CH>>
 {
     /// Get internal sample data as `Vec` of audio frames.
     fn from(audio: Audio<Chan, CH>) -> Self {
-        let audio: Vec<Frame<Chan, CH>> = audio.frames.into();
-        audio.into()
+        audio.frames.into_boxed_slice()
     }
 }
 
@@ -355,7 +1198,8 @@ impl<const CH: usize> From<Audio<Ch16, CH>> for Box<[i16]> {
     /// Get internal sample data as boxed slice of *i16*.
     #[allow(unsafe_code)]
     fn from(audio: Audio<Ch16, CH>) -> Self {
-        let mut frames: Vec<Frame<Ch16, CH>> = audio.frames.into();
+        let mut frames: Vec<Frame<Ch16, CH>> =
+            audio.frames.into_boxed_slice().into();
         let capacity = frames.len() * size_of::<Frame<Ch16, CH>>() / 2;
         let buffer: Box<[i16]> = unsafe {
             let ptr = frames.as_mut_ptr() as *mut i16;
@@ -369,7 +1213,8 @@ impl<const CH: usize> From<Audio<Ch24, CH>> for Box<[u8]> {
     /// Get internal sample data as boxed slice of *u8*.
     #[allow(unsafe_code)]
     fn from(audio: Audio<Ch24, CH>) -> Self {
-        let mut frames: Vec<Frame<Ch24, CH>> = audio.frames.into();
+        let mut frames: Vec<Frame<Ch24, CH>> =
+            audio.frames.into_boxed_slice().into();
         let capacity = frames.len() * size_of::<Frame<Ch24, CH>>() / 3;
         let buffer: Box<[u8]> = unsafe {
             let ptr = frames.as_mut_ptr() as *mut u8;
@@ -383,7 +1228,8 @@ impl<const CH: usize> From<Audio<Ch32, CH>> for Box<[f32]> {
     /// Get internal sample data as boxed slice of *f32*.
     #[allow(unsafe_code)]
     fn from(audio: Audio<Ch32, CH>) -> Self {
-        let mut frames: Vec<Frame<Ch32, CH>> = audio.frames.into();
+        let mut frames: Vec<Frame<Ch32, CH>> =
+            audio.frames.into_boxed_slice().into();
         let capacity = frames.len() * size_of::<Frame<Ch32, CH>>() / 4;
         let buffer: Box<[f32]> = unsafe {
             let ptr = frames.as_mut_ptr() as *mut f32;
@@ -397,7 +1243,8 @@ impl<const CH: usize> From<Audio<Ch64, CH>> for Box<[f64]> {
     /// Get internal sample data as boxed slice of *f64*.
     #[allow(unsafe_code)]
     fn from(audio: Audio<Ch64, CH>) -> Self {
-        let mut frames: Vec<Frame<Ch64, CH>> = audio.frames.into();
+        let mut frames: Vec<Frame<Ch64, CH>> =
+            audio.frames.into_boxed_slice().into();
         let capacity = frames.len() * size_of::<Frame<Ch64, CH>>() / 8;
         let buffer: Box<[f64]> = unsafe {
             let ptr = frames.as_mut_ptr() as *mut f64;
@@ -406,3 +1253,639 @@ impl<const CH: usize> From<Audio<Ch64, CH>> for Box<[f64]> {
         buffer
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::f32::consts::{FRAC_1_SQRT_2, SQRT_2};
+
+    #[test]
+    fn append_resampled() {
+        let frames = (0..1000)
+            .map(|i| {
+                let x = (i as f32 * 0.05).sin();
+                Frame::<Ch32, 2>::new(Ch32::new(x), Ch32::new(-x))
+            })
+            .collect::<Vec<_>>();
+        let whole = Audio::with_frames(44_100, frames.clone());
+        let head = Audio::with_frames(44_100, frames[..373].to_vec());
+        let tail = Audio::with_frames(44_100, frames[373..].to_vec());
+        let hz = NonZeroU32::new(48_000).unwrap();
+
+        let mut expected = Vec::<Frame<Ch32, 2>>::new();
+        let mut stream = Stream::new(48_000);
+        stream.pipe(&whole, VecSink::new(hz, 2000, &mut expected));
+        stream.flush(VecSink::new(hz, 2000, &mut expected));
+
+        let mut joined = Audio::<Ch32, 2>::with_frames(48_000, Vec::new());
+        let mut stream = Stream::new(48_000);
+        joined.append_resampled(&head, &mut stream);
+        joined.append_resampled(&tail, &mut stream);
+        let mut joined: Vec<_> = joined.into();
+        stream.flush(VecSink::new(hz, 2000, &mut joined));
+
+        assert!(!joined.is_empty());
+        assert_eq!(joined, expected);
+    }
+
+    #[test]
+    fn converter() {
+        let mut converter = Converter::<Ch16, Ch32, 2>::new();
+        let audio = Audio::<Ch16, 2>::with_silence(48_000, 256);
+        let ptr = converter.convert(&audio).as_slice().as_ptr();
+        for i in 0..16 {
+            let audio =
+                Audio::<Ch16, 2>::with_i16_buffer(44_100, vec![i * 1000; 512]);
+            let output = converter.convert(&audio);
+            assert_eq!(output.as_slice().as_ptr(), ptr);
+            assert_eq!(output.len(), 256);
+            assert_eq!(output.sample_rate().get(), 44_100);
+            assert_eq!(
+                output.get(255).unwrap(),
+                audio.get(255).unwrap().to::<Ch32, 2>()
+            );
+        }
+
+        // Shorter buffers reuse the storage too.
+        let audio = Audio::<Ch16, 2>::with_silence(48_000, 100);
+        let output = converter.convert(&audio);
+        assert_eq!(output.as_slice().as_ptr(), ptr);
+        assert_eq!(output.len(), 100);
+    }
+
+    #[test]
+    fn pad_to_pow2() {
+        let mut audio = Audio::<Ch16, 2>::with_i16_buffer(48_000, vec![1; 200]);
+        assert_eq!(audio.next_pow2_len(), 128);
+        audio.pad_to_pow2();
+        assert_eq!(audio.len(), 128);
+        assert_eq!(
+            audio.get(99).unwrap(),
+            Frame::<Ch16, 2>::new(Ch16::new(1), Ch16::new(1))
+        );
+        assert_eq!(audio.get(100).unwrap(), Frame::default());
+
+        let mut audio = Audio::<Ch16, 2>::with_silence(48_000, 128);
+        assert_eq!(audio.next_pow2_len(), 128);
+        audio.pad_to_pow2();
+        assert_eq!(audio.len(), 128);
+
+        let mut audio = Audio::<Ch16, 2>::with_silence(48_000, 0);
+        assert_eq!(audio.next_pow2_len(), 0);
+        audio.pad_to_pow2();
+        assert!(audio.is_empty());
+    }
+
+    #[test]
+    fn to_packed_24le() {
+        let audio = Audio::<Ch32, 2>::with_f32_buffer(
+            48_000,
+            vec![1.0, -1.0, 0.0, 2.0, -0.5, 0.5],
+        );
+        assert_eq!(
+            audio.to_packed_24le(),
+            [
+                0xFF, 0xFF, 0x7F, 0x00, 0x00, 0x80, 0x00, 0x00, 0x00, 0xFF,
+                0xFF, 0x7F, 0x00, 0x00, 0xC0, 0xFF, 0xFF, 0x3F,
+            ]
+        );
+    }
+
+    #[test]
+    fn from_frames_iter() {
+        let audio = Audio::<Ch16, 2>::from_frames_iter(
+            48_000,
+            (0..4).map(|i| {
+                let x = Ch16::new(i * 100);
+                Frame::<Ch16, 2>::new(x, -x)
+            }),
+        );
+        assert_eq!(audio.sample_rate().get(), 48_000);
+        // Negating a `Ch16` rounds away from zero.
+        let expected = Audio::<Ch16, 2>::with_i16_buffer(
+            48_000,
+            vec![0, -1, 100, -101, 200, -201, 300, -301],
+        );
+        assert_eq!(audio.as_slice(), expected.as_slice());
+    }
+
+    #[test]
+    fn from_device_buffer_i16() {
+        let (audio, leftover) =
+            Audio::<Ch16, 2>::from_device_buffer_i16(48_000, &[1, 2, 3, 4, 5]);
+        assert_eq!(audio.len(), 2);
+        assert_eq!(
+            audio.get(1).unwrap(),
+            Frame::<Ch16, 2>::new(Ch16::new(3), Ch16::new(4))
+        );
+        assert_eq!(leftover, [5]);
+
+        let (audio, leftover) =
+            Audio::<Ch16, 2>::from_device_buffer_i16(48_000, &[1, 2]);
+        assert_eq!(audio.len(), 1);
+        assert!(leftover.is_empty());
+    }
+
+    #[test]
+    fn overwrite() {
+        let mut audio = Audio::<Ch16, 1>::with_i16_buffer(48_000, vec![1; 8]);
+        let src = Audio::<Ch16, 1>::with_i16_buffer(48_000, vec![2, 3, 4]);
+        audio.overwrite(2, &src);
+        assert_eq!(audio.as_i16_slice(), [1, 1, 2, 3, 4, 1, 1, 1]);
+
+        // Clamped at the end.
+        audio.overwrite(6, &src);
+        assert_eq!(audio.as_i16_slice(), [1, 1, 2, 3, 4, 1, 2, 3]);
+        audio.overwrite(100, &src);
+        assert_eq!(audio.len(), 8);
+    }
+
+    #[test]
+    fn blend() {
+        let mut audio =
+            Audio::<Ch16, 1>::with_i16_buffer(48_000, vec![100, -200, 300]);
+        let other = Audio::<Ch16, 1>::with_i16_buffer(48_000, vec![50, 50]);
+        audio.blend(&other, Blend::Max);
+        assert_eq!(audio.as_i16_slice(), [100, 50, 300]);
+        audio.blend(&other, Blend::Overwrite);
+        assert_eq!(audio.as_i16_slice(), [50, 50, 300]);
+    }
+
+    #[test]
+    fn cast() {
+        let samples = vec![0, 1, -1, 12_345, i16::MIN, i16::MAX];
+        let audio = Audio::<Ch16, 2>::with_i16_buffer(44_100, samples.clone());
+        let float: Audio<Ch32, 2> = audio.cast();
+        assert_eq!(float.sample_rate(), audio.sample_rate());
+        assert_eq!(float.get(2).unwrap().channels()[1], Ch32::new(1.0));
+        assert_eq!(float.cast::<Ch16>().as_slice(), audio.as_slice());
+
+        // Same as converting without changing the sample rate.
+        let converted = Audio::<Ch32, 2>::with_audio(44_100, &audio);
+        assert_eq!(float.as_slice(), converted.as_slice());
+    }
+
+    #[test]
+    fn convert_to() {
+        // 48 kHz 32-bit float mono to 16 kHz 16-bit mono.
+        let step = 2.0 * core::f32::consts::PI * 1_000.0 / 48_000.0;
+        let frames = (0..4_800)
+            .map(|i| {
+                Frame::<Ch32, 1>::new(Ch32::new(0.5 * (i as f32 * step).sin()))
+            })
+            .collect::<Vec<_>>();
+        let audio = Audio::with_frames(48_000, frames);
+        let converted = audio.convert_to::<Ch16, 1>(16_000);
+        assert_eq!(converted.sample_rate().get(), 16_000);
+        assert_eq!(converted.len(), 1_600);
+        let chained =
+            Audio::<Ch32, 1>::with_audio(16_000, &audio).cast::<Ch16>();
+        assert_eq!(converted.as_slice(), chained.as_slice());
+        let peak = converted
+            .iter()
+            .skip(100)
+            .map(|frame| frame.channels()[0].to_f32().abs())
+            .fold(0.0, f32::max);
+        assert!((peak - 0.5).abs() < 0.01, "{}", peak);
+
+        // 16-bit stereo to 32-bit float mono, at the same sample rate.
+        let audio = Audio::<Ch16, 2>::with_i16_buffer(
+            44_100,
+            vec![16_384, 16_384, -8_192, 8_192, i16::MAX, 0],
+        );
+        let mono = audio.convert_to::<Ch32, 1>(44_100);
+        let chained = Audio::<Ch32, 2>::with_audio(44_100, &audio);
+        let chained = Audio::<Ch32, 1>::with_audio(44_100, &chained);
+        assert_eq!(mono.as_slice(), chained.as_slice());
+        assert!(mono.get(1).unwrap().channels()[0].to_f32().abs() < 1e-4);
+
+        // Downmixing while resampling.
+        let stereo = Audio::<Ch32, 2>::with_silence(44_100, 4_410);
+        let mono = stereo.convert_to::<Ch32, 1>(48_000);
+        assert_eq!(mono.sample_rate().get(), 48_000);
+        assert_eq!(mono.len(), 4_800);
+    }
+
+    #[test]
+    fn compatibility() {
+        let a = Audio::<Ch32, 2>::with_silence(48_000, 100);
+        let b = Audio::<Ch32, 2>::with_silence(44_100, 100);
+        let c = Audio::<Ch32, 2>::with_silence(48_000, 50);
+        assert!(a.is_compatible_with(&a));
+        assert!(!a.is_compatible_with(&b));
+        assert!(a.is_compatible_with(&c));
+        assert_eq!(a.compatibility(&a), Compatibility::Compatible);
+        assert_eq!(a.compatibility(&b), Compatibility::DifferentSampleRate);
+        assert_eq!(a.compatibility(&c), Compatibility::DifferentLength);
+    }
+
+    #[test]
+    fn push_silence() {
+        let mut audio =
+            Audio::<Ch16, 2>::with_i16_buffer(48_000, vec![1, 2, 3, 4]);
+        audio.push_silence(100);
+        assert_eq!(audio.len(), 102);
+        assert_eq!(audio.get(0).unwrap().channels()[1], Ch16::new(2));
+        assert!(audio.as_slice()[2..]
+            .iter()
+            .all(|frame| frame.channels().iter().all(|x| *x == Ch16::MID)));
+        audio.push_silence_duration(Duration::from_millis(10));
+        assert_eq!(audio.len(), 582);
+    }
+
+    #[test]
+    fn to_mono_compat() {
+        let mono = |audio: Audio<Ch32, 1>| {
+            audio
+                .iter()
+                .map(|f| f.channels()[0].to_f32())
+                .collect::<Vec<_>>()
+        };
+        let assert_close = |a: Vec<f32>, b: &[f32]| {
+            for (a, b) in a.iter().zip(b) {
+                assert!((a - b).abs() < 1e-6, "{} {}", a, b);
+            }
+        };
+
+        // In-phase stereo rises by 3 dB, out-of-phase cancels, and one
+        // side alone drops by 3 dB.
+        let audio = Audio::<Ch32, 2>::with_f32_buffer(
+            48_000,
+            [0.5, 0.5, 0.5, -0.5, 0.5, 0.0],
+        );
+        let expected = [0.5 * SQRT_2, 0.0, 0.5 * FRAC_1_SQRT_2];
+        assert_close(mono(audio.to_mono_compat()), &expected);
+
+        // Mono is unchanged.
+        let audio = Audio::<Ch32, 1>::with_f32_buffer(48_000, [0.25, -0.5]);
+        assert_close(mono(audio.to_mono_compat()), &[0.25, -0.5]);
+
+        // 5.1 leaves out the LFE, and compensates for five channels.
+        let audio = Audio::<Ch32, 6>::with_f32_buffer(
+            48_000,
+            [0.2, 0.2, 0.2, 1.0, 0.2, 0.2],
+        );
+        assert_close(mono(audio.to_mono_compat()), &[5.0f32.sqrt() * 0.2]);
+
+        // Integer channels clip past full scale, but floats don't.
+        let audio =
+            Audio::<Ch16, 2>::with_i16_buffer(48_000, vec![i16::MAX; 2]);
+        let mono16 = audio.to_mono_compat().get(0).unwrap();
+        assert_eq!(mono16.channels()[0], Ch16::MAX);
+        let peak = mono(audio.cast::<Ch32>().to_mono_compat())[0];
+        assert!((peak - SQRT_2).abs() < 1e-4);
+    }
+
+    #[test]
+    fn split_at() {
+        let samples = [0.0, 0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7];
+        let audio = Audio::<Ch32, 2>::with_f32_buffer(48_000, samples);
+        let (head, tail) = audio.split_at(1);
+        assert_eq!(head.len(), 1);
+        assert_eq!(tail.len(), 3);
+        assert_eq!(tail.sample_rate(), head.sample_rate());
+        let joined = head.iter().chain(tail.iter()).cloned();
+        let original = Audio::<Ch32, 2>::with_f32_buffer(48_000, samples);
+        assert!(joined.eq(original.iter().cloned()));
+
+        let (head, tail) = original.split_at(4);
+        assert_eq!((head.len(), tail.len()), (4, 0));
+    }
+
+    #[test]
+    fn to_rate() {
+        let frames = (0..480)
+            .map(|i| Frame::<Ch32, 1>::new(Ch32::new((i as f32 * 0.1).sin())))
+            .collect::<Vec<_>>();
+        let audio = Audio::with_frames(48_000, frames);
+
+        let relabeled = audio.to_rate(44_100, RateMode::Relabel);
+        assert_eq!(relabeled.sample_rate().get(), 44_100);
+        assert_eq!(relabeled.as_slice(), audio.as_slice());
+
+        let resampled = audio.to_rate(44_100, RateMode::Resample);
+        assert_eq!(resampled.sample_rate().get(), 44_100);
+        assert_eq!(resampled.len(), 441);
+    }
+
+    #[test]
+    fn tags() {
+        let mut audio = Audio::<Ch32, 2>::with_silence(48_000, 10);
+        assert!(audio.tags().is_empty());
+        audio.set_tag("title", "Take");
+        audio.set_tag("take", "3");
+        audio.set_tag("title", "Intro");
+        assert_eq!(audio.get_tag("title"), Some("Intro"));
+        assert_eq!(audio.get_tag("device"), None);
+        assert_eq!(audio.tags().len(), 2);
+
+        // Derived buffers keep the tags.
+        let tags = audio.tags().clone();
+        assert_eq!(Audio::<Ch16, 1>::with_audio(44_100, &audio).tags(), &tags);
+        assert_eq!(audio.to_rate(8_000, RateMode::Resample).tags(), &tags);
+        assert_eq!(audio.to_rate(8_000, RateMode::Relabel).tags(), &tags);
+        assert_eq!(audio.cast::<Ch16>().tags(), &tags);
+        assert_eq!(audio.convert_to::<Ch16, 1>(48_000).tags(), &tags);
+        assert_eq!(audio.convert_to::<Ch16, 1>(8_000).tags(), &tags);
+        assert_eq!(audio.convert_to::<Ch16, 6>(8_000).tags(), &tags);
+        assert_eq!(audio.stretch(2.0).tags(), &tags);
+
+        let (head, tail) = audio.split_at(4);
+        assert_eq!(head.tags(), tail.tags());
+        assert_eq!(tail.get_tag("take"), Some("3"));
+    }
+
+    #[test]
+    fn offset_binary() {
+        let audio = Audio::<Ch16, 2>::with_i16_buffer(
+            48_000,
+            vec![0, i16::MIN, i16::MAX, -1],
+        );
+        let unsigned = audio.to_offset_binary();
+        assert_eq!(unsigned, [0x8000, 0x0000, 0xFFFF, 0x7FFF]);
+        let audio = Audio::<Ch16, 1>::with_frames(
+            48_000,
+            vec![Frame::<Ch16, 1>::new(Ch16::MID)],
+        );
+        assert_eq!(audio.to_offset_binary(), [0x8000]);
+
+        let audio = Audio::<Ch16, 2>::from_offset_binary(48_000, &unsigned);
+        assert_eq!(
+            audio.as_slice(),
+            Audio::<Ch16, 2>::with_i16_buffer(
+                48_000,
+                vec![0, i16::MIN, i16::MAX, -1]
+            )
+            .as_slice()
+        );
+    }
+
+    #[test]
+    fn channel_count_mismatch() {
+        let error = Audio::<Ch32, 2>::try_with_f32_buffer(48_000, vec![0.0; 5])
+            .unwrap_err();
+        assert_eq!(
+            error,
+            AudioError::ChannelCountMismatch {
+                samples: 5,
+                channels: 2
+            }
+        );
+        assert_eq!(
+            alloc::format!("{}", error),
+            "5 interleaved samples can't be split into 2-channel frames \
+             (1 left over)"
+        );
+
+        let audio = Audio::<Ch16, 2>::try_with_i16_buffer(48_000, vec![0; 6]);
+        assert_eq!(audio.unwrap().len(), 3);
+        assert!(Audio::<Ch64, 3>::try_with_f64_buffer(48_000, vec![0.0; 4])
+            .is_err());
+    }
+
+    #[test]
+    fn u8_buffer() {
+        // 12 bytes of 24-bit stereo is 2 frames.
+        let bytes = vec![1, 0, 0, 2, 0, 0, 3, 0, 0, 4, 0, 0];
+        let mut audio = Audio::<Ch24, 2>::with_u8_buffer(48_000, bytes.clone());
+        assert_eq!(audio.len(), 2);
+        assert_eq!(audio.as_u8_slice(), &bytes[..]);
+        let audio = Audio::<Ch24, 2>::try_with_u8_buffer(48_000, bytes);
+        assert_eq!(audio.unwrap().len(), 2);
+
+        assert_eq!(
+            Audio::<Ch24, 2>::try_with_u8_buffer(48_000, vec![0; 9])
+                .unwrap_err(),
+            AudioError::ChannelCountMismatch {
+                samples: 3,
+                channels: 2
+            }
+        );
+        assert_eq!(
+            Audio::<Ch24, 1>::try_with_u8_buffer(48_000, vec![0; 4])
+                .unwrap_err(),
+            AudioError::Malformed("partial 24-bit sample")
+        );
+    }
+
+    #[test]
+    fn truncate_clear() {
+        let mut audio =
+            Audio::<Ch16, 1>::with_i16_buffer(48_000, vec![1, 2, 3, 4, 5]);
+        let ptr = audio.as_slice().as_ptr();
+        audio.truncate(8);
+        assert_eq!(audio.len(), 5);
+        audio.truncate(3);
+        assert_eq!(audio.len(), 3);
+        assert_eq!(audio.as_slice().as_ptr(), ptr);
+        assert_eq!(audio.get(2).unwrap(), Frame::<Ch16, 1>::new(Ch16::new(3)));
+        assert!(audio.get(3).is_none());
+
+        audio.clear();
+        assert!(audio.is_empty());
+        assert!(audio.frames.capacity() >= 5);
+    }
+
+    #[test]
+    fn broadcast() {
+        let mono = Audio::<Ch16, 1>::with_i16_buffer(44_100, vec![1, -2, 3]);
+        let audio = mono.broadcast::<6>();
+        assert_eq!(audio.len(), 3);
+        assert_eq!(audio.sample_rate().get(), 44_100);
+        for (frame, mono) in audio.iter().zip(mono.iter()) {
+            for chan in frame.channels() {
+                assert_eq!(*chan, mono.channels()[0]);
+            }
+        }
+    }
+
+    #[test]
+    fn split_join() {
+        let audio = Audio::<Ch16, 2>::with_i16_buffer(
+            22_050,
+            vec![1, -1, 2, -2, 3, -3],
+        );
+        let (left, right) = audio.split();
+        assert_eq!(left.sample_rate().get(), 22_050);
+        assert_eq!(left.get(2).unwrap(), Frame::<Ch16, 1>::new(Ch16::new(3)));
+        assert_eq!(right.get(1).unwrap(), Frame::<Ch16, 1>::new(Ch16::new(-2)));
+
+        let joined = Audio::join(&left, &right);
+        assert_eq!(joined.sample_rate(), audio.sample_rate());
+        assert_eq!(joined.as_slice(), audio.as_slice());
+    }
+
+    #[test]
+    fn duration() {
+        let audio = Audio::<Ch16, 2>::with_silence_duration(
+            44_100,
+            Duration::from_secs(2),
+        );
+        assert_eq!(audio.len(), 88_200);
+        let audio = Audio::<Ch16, 2>::with_silence_duration(
+            44_100,
+            Duration::from_millis(10),
+        );
+        assert_eq!(audio.len(), 441);
+
+        let audio = Audio::<Ch32, 1>::with_capacity_duration(
+            48_000,
+            Duration::from_secs(2),
+        );
+        assert!(audio.is_empty());
+        assert!(audio.frames.capacity() >= 96_000);
+    }
+
+    #[test]
+    fn iter_timed() {
+        let audio = Audio::<Ch16, 2>::with_silence(48_000, 96_001);
+        let times = audio.iter_timed().map(|(t, _)| t).collect::<Vec<_>>();
+        assert_eq!(times.len(), 96_001);
+        assert_eq!(times[0], Duration::ZERO);
+        assert_eq!(times[24], Duration::from_micros(500));
+        assert_eq!(times[48_000], Duration::from_secs(1));
+        assert_eq!(times[96_000], Duration::from_secs(2));
+
+        let audio = Audio::<Ch16, 1>::with_silence(44_100, 44_101);
+        let (time, _) = audio.iter_timed().nth(44_100).unwrap();
+        assert_eq!(time, Duration::from_secs(1));
+        let (time, _) = audio.iter_timed().nth(1).unwrap();
+        assert_eq!(time, Duration::from_nanos(22_675));
+    }
+
+    #[test]
+    fn infer_sample_rate() {
+        let second = Duration::from_secs(1);
+        assert_eq!(super::infer_sample_rate(48_000, second), 48_000);
+        let duration = Duration::from_millis(2500);
+        assert_eq!(super::infer_sample_rate(110_250, duration), 44_100);
+        let duration = Duration::from_millis(10);
+        assert_eq!(super::infer_sample_rate(441, duration), 44_100);
+        let duration = Duration::from_secs(3);
+        assert_eq!(super::infer_sample_rate(100, duration), 33);
+    }
+
+    #[test]
+    fn guess_channels() {
+        // 16-bit: 1000 bytes is 250 stereo frames, but not whole 3-channel
+        // frames.
+        assert_eq!(super::guess_channels(1000, 16, &[3, 2]), Some(2));
+        assert_eq!(super::guess_channels(1000, 16, &[3, 6]), None);
+        assert_eq!(super::guess_channels(1002, 24, &[2, 1]), Some(2));
+        assert_eq!(super::guess_channels(1000, 24, &[2, 0]), None);
+        assert_eq!(super::guess_channels(1000, 8, &[]), None);
+    }
+
+    #[test]
+    fn frames_ms() {
+        assert_eq!(frames_to_ms(480, 48_000), 10.0);
+        assert_eq!(ms_to_frames(10.0, 48_000), 480);
+        assert_eq!(ms_to_frames(10.0, 44_100), 441);
+        let ms = frames_to_ms(256, 44_100);
+        assert!((ms - 5.805).abs() < 0.001);
+        assert_eq!(ms_to_frames(ms, 44_100), 256);
+    }
+
+    #[test]
+    fn frames_f32() {
+        let audio = Audio::<Ch16, 2>::with_i16_buffer(
+            48_000,
+            vec![0, 16_384, -1, i16::MIN],
+        );
+        let frames = audio.frames_f32().collect::<Vec<_>>();
+        assert_eq!(frames.len(), 2);
+        for (samples, frame) in frames.iter().zip(audio.iter()) {
+            for (sample, chan) in samples.iter().zip(frame.channels()) {
+                assert_eq!(*sample, chan.to_f32());
+            }
+        }
+        assert_eq!(frames[1][1], -1.0);
+    }
+
+    #[test]
+    fn ch24_i32_roundtrip() {
+        let samples = [-1, 0, 8_388_607, -8_388_608, 0x12_3456, -0x12_3456];
+        let audio = Audio::<Ch24, 2>::from_i32_buffer(96_000, &samples);
+        assert_eq!(audio.len(), 3);
+        assert_eq!(audio.get(0).unwrap().channels()[0], Ch24::new(-1));
+        assert_eq!(audio.get(1).unwrap().channels()[1], Ch24::MIN);
+        assert_eq!(audio.to_i32_buffer(), samples);
+
+        // Out of range values are clamped.
+        let audio = Audio::<Ch24, 1>::from_i32_buffer(96_000, &[i32::MAX]);
+        assert_eq!(audio.to_i32_buffer(), [8_388_607]);
+
+        let audio = Audio::<Ch24, 2>::try_from_i32_buffer(96_000, &samples);
+        assert_eq!(audio.unwrap().len(), 3);
+        assert_eq!(
+            Audio::<Ch24, 2>::try_from_i32_buffer(96_000, &samples[..5])
+                .unwrap_err(),
+            AudioError::ChannelCountMismatch {
+                samples: 5,
+                channels: 2
+            }
+        );
+    }
+
+    #[test]
+    fn ch32i_i32_buffer() {
+        let samples = vec![i32::MIN, i32::MAX, 0, -1];
+        let mut audio = Audio::<Ch32i, 2>::with_i32_buffer(96_000, samples);
+        assert_eq!(audio.len(), 2);
+        assert_eq!(audio.get(0).unwrap().channels()[0], Ch32i::MIN);
+        assert_eq!(audio.get(0).unwrap().channels()[1], Ch32i::MAX);
+        assert_eq!(audio.as_i32_slice(), &[i32::MIN, i32::MAX, 0, -1][..]);
+
+        // Extremes survive conversion to float and back.
+        let float = Audio::<Ch32, 2>::with_audio(96_000, &audio);
+        let mut back = Audio::<Ch32i, 2>::with_audio(96_000, &float);
+        assert_eq!(back.as_i32_slice()[..2], [i32::MIN, i32::MAX]);
+
+        assert!(
+            Audio::<Ch32i, 2>::try_with_i32_buffer(96_000, vec![0; 3]).is_err()
+        );
+    }
+
+    #[test]
+    fn ch24_copy_to_i32() {
+        let mut audio = Audio::<Ch24, 2>::from_i32_buffer(
+            48_000,
+            &[-1, 0x12_3456, -0x80_0000, 1],
+        );
+        let mut out = [0; 4];
+        audio.copy_to_i32_slice(&mut out);
+        let samples = audio
+            .iter()
+            .flat_map(|f| f.channels().iter())
+            .map(|chan| i32::from(*chan))
+            .collect::<Vec<_>>();
+        assert_eq!(out.as_slice(), samples);
+        assert_eq!(out, [-1, 0x12_3456, -0x80_0000, 1]);
+
+        // Odd offsets into the byte buffer still expand correctly.
+        audio.as_u8_slice()[3..6].copy_from_slice(&[0x01, 0x00, 0x80]);
+        audio.copy_to_i32_slice(&mut out);
+        assert_eq!(out[1], i32::from(audio.get(0).unwrap().channels()[1]));
+    }
+
+    #[test]
+    fn interleave_mixed() {
+        let main = Audio::<Ch16, 1>::with_i16_buffer(48_000, vec![16_384, -1]);
+        let lfe = Audio::<Ch24, 1>::from_i32_buffer(48_000, &[-4_194_304, 0]);
+        let audio = Audio::<Ch32, 6>::interleave_mixed([
+            &main, &main, &main, &lfe, &main, &main,
+        ]);
+        assert_eq!(audio.len(), 2);
+        assert_eq!(audio.sample_rate().get(), 48_000);
+        let frame = audio.get(0).unwrap();
+        for (i, chan) in frame.channels().iter().enumerate() {
+            let expected = if i == 3 {
+                Ch24::new(-4_194_304).to_f32()
+            } else {
+                Ch16::new(16_384).to_f32()
+            };
+            assert_eq!(chan.to_f32(), expected);
+        }
+        assert!((frame.channels()[3].to_f32() + 0.5).abs() < 1e-6);
+    }
+}
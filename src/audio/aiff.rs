@@ -0,0 +1,273 @@
+// Copyright © 2020-2022 The Fon Contributors.
+//
+// Licensed under any of:
+// - Apache License, Version 2.0 (https://www.apache.org/licenses/LICENSE-2.0)
+// - MIT License (https://mit-license.org/)
+// - Boost Software License, Version 1.0 (https://www.boost.org/LICENSE_1_0.txt)
+// At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
+// LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
+
+use crate::chan::{Ch24, Channel};
+use crate::error::AudioError;
+use crate::frame::Frame;
+use crate::Audio;
+
+use alloc::vec::Vec;
+use core::convert::TryInto;
+
+/// Exponent bias of an 80-bit IEEE 754 extended precision float.
+const EXTENDED_BIAS: i32 = 16_383;
+
+/// Read a big-endian `u16` at `offset`.
+fn be_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_be_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+/// Read a big-endian `u32` at `offset`.
+fn be_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+/// Decode an 80-bit extended float sample rate, rounded to the nearest hertz.
+fn decode_rate(bytes: &[u8]) -> Result<u32, AudioError> {
+    let exponent = be_u16(bytes, 0);
+    let mantissa = u64::from_be_bytes(bytes[2..10].try_into().unwrap());
+    if exponent & 0x8000 != 0 {
+        return Err(AudioError::Malformed("negative sample rate"));
+    }
+    let shift = i32::from(exponent) - EXTENDED_BIAS - 63;
+    let rate = if mantissa == 0 || shift < -64 {
+        0
+    } else if shift < 0 {
+        let shift = -shift as u32;
+        let rounding = mantissa.checked_shr(shift - 1).unwrap_or(0) & 1;
+        mantissa.checked_shr(shift).unwrap_or(0) + rounding
+    } else if mantissa.leading_zeros() as i32 >= shift {
+        mantissa << shift
+    } else {
+        u64::MAX
+    };
+    rate.try_into()
+        .map_err(|_| AudioError::Malformed("sample rate out of range"))
+}
+
+/// Encode a sample rate as an 80-bit extended float.
+fn encode_rate(rate: u32) -> [u8; 10] {
+    let zeros = u64::from(rate).leading_zeros();
+    let mantissa = u64::from(rate) << zeros;
+    let exponent = (EXTENDED_BIAS + 63 - zeros as i32) as u16;
+    let mut bytes = [0; 10];
+    bytes[..2].copy_from_slice(&exponent.to_be_bytes());
+    bytes[2..].copy_from_slice(&mantissa.to_be_bytes());
+    bytes
+}
+
+impl<Chan, const CH: usize> Audio<Chan, CH>
+where
+    Chan: Channel + From<Ch24>,
+{
+    /// Load audio from the bytes of an AIFF or uncompressed AIFF-C file
+    /// (requires the `aiff` feature).
+    ///
+    /// Samples up to 24 bits deep are supported.  The sample rate is rounded
+    /// to the nearest hertz.
+    ///
+    /// # Errors
+    /// If the file is malformed, compressed, more than 24 bits deep, or
+    /// doesn't have `CH` channels.
+    pub fn load_aiff(bytes: &[u8]) -> Result<Self, AudioError> {
+        if bytes.len() < 12 || &bytes[..4] != b"FORM" {
+            return Err(AudioError::Malformed("missing FORM chunk"));
+        }
+        let compressed = match &bytes[8..12] {
+            b"AIFF" => false,
+            b"AIFC" => true,
+            _ => return Err(AudioError::Malformed("not an AIFF file")),
+        };
+        let end = bytes.len().min(8 + be_u32(bytes, 4) as usize);
+
+        // Find the common and sound data chunks.
+        let mut common = None;
+        let mut sound = None;
+        let mut offset = 12;
+        while offset + 8 <= end {
+            let size = be_u32(bytes, offset + 4) as usize;
+            let body = bytes
+                .get(offset + 8..offset + 8 + size)
+                .ok_or(AudioError::Malformed("truncated chunk"))?;
+            match &bytes[offset..offset + 4] {
+                b"COMM" => common = Some(body),
+                b"SSND" => sound = Some(body),
+                _ => {}
+            }
+            offset += 8 + size + (size & 1);
+        }
+
+        let common = common.ok_or(AudioError::Malformed("missing COMM"))?;
+        if common.len() < 18 {
+            return Err(AudioError::Malformed("truncated COMM chunk"));
+        }
+        if compressed
+            && common.get(18..22) != Some(b"NONE")
+            && common.get(18..22) != Some(b"twos")
+        {
+            return Err(AudioError::Malformed("compressed AIFF-C"));
+        }
+        let channels = usize::from(be_u16(common, 0));
+        let len = be_u32(common, 2) as usize;
+        let bits = be_u16(common, 6);
+        let hz = decode_rate(&common[8..18])?;
+        if channels != CH {
+            return Err(AudioError::Malformed("channel count doesn't match"));
+        }
+        if bits == 0 || bits > 24 {
+            return Err(AudioError::Malformed("unsupported bit depth"));
+        }
+        if hz == 0 {
+            return Err(AudioError::ZeroSampleRate);
+        }
+
+        // Samples are big-endian and left-justified in whole bytes.
+        let width = usize::from(bits + 7) / 8;
+        let data = match sound {
+            Some(sound) if sound.len() >= 8 => {
+                sound.get(8 + be_u32(sound, 0) as usize..).unwrap_or(&[])
+            }
+            _ => &[],
+        };
+        if data.len() / (width * CH) < len {
+            return Err(AudioError::Malformed("truncated sound data"));
+        }
+        let frames = data
+            .chunks_exact(width * CH)
+            .take(len)
+            .map(|samples| {
+                let mut frame = Frame::<Chan, CH>::default();
+                let channels = frame.channels_mut();
+                for (chan, sample) in
+                    channels.iter_mut().zip(samples.chunks(width))
+                {
+                    let mut bytes = [0; 4];
+                    bytes[..width].copy_from_slice(sample);
+                    let value = i32::from_be_bytes(bytes) >> 8;
+                    *chan = Chan::from(Ch24::new(value));
+                }
+                frame
+            })
+            .collect::<Vec<_>>();
+
+        Ok(Audio::with_frames(hz, frames))
+    }
+}
+
+impl<Chan, const CH: usize> Audio<Chan, CH>
+where
+    Chan: Channel,
+    Ch24: From<Chan>,
+{
+    /// Save the audio as the bytes of an AIFF file with `bits` bits per
+    /// sample (requires the `aiff` feature).
+    ///
+    /// # Panics
+    /// If `bits` isn't 8, 16 or 24, or the audio is too long or has too many
+    /// channels to fit in an AIFF file.
+    pub fn save_aiff(&self, bits: u16) -> Vec<u8> {
+        assert!(matches!(bits, 8 | 16 | 24));
+        let width = usize::from(bits / 8);
+        let channels: u16 = CH.try_into().expect("too many channels");
+        let len: u32 = self.len().try_into().expect("too many frames");
+        let data_len = self.len() * CH * width;
+        let sound_len: u32 = (8 + data_len).try_into().expect("too long");
+        let form_len = 4 + (8 + 18) + (8 + sound_len) + (sound_len & 1);
+
+        let mut bytes = Vec::with_capacity(8 + form_len as usize);
+        bytes.extend_from_slice(b"FORM");
+        bytes.extend_from_slice(&form_len.to_be_bytes());
+        bytes.extend_from_slice(b"AIFF");
+
+        bytes.extend_from_slice(b"COMM");
+        bytes.extend_from_slice(&18u32.to_be_bytes());
+        bytes.extend_from_slice(&channels.to_be_bytes());
+        bytes.extend_from_slice(&len.to_be_bytes());
+        bytes.extend_from_slice(&bits.to_be_bytes());
+        bytes.extend_from_slice(&encode_rate(self.sample_rate().get()));
+
+        bytes.extend_from_slice(b"SSND");
+        bytes.extend_from_slice(&sound_len.to_be_bytes());
+        bytes.extend_from_slice(&[0; 8]);
+        for frame in self.iter() {
+            for chan in frame.channels() {
+                let value = i32::from(Ch24::from(*chan)) << 8;
+                bytes.extend_from_slice(&value.to_be_bytes()[..width]);
+            }
+        }
+        if sound_len & 1 != 0 {
+            bytes.push(0);
+        }
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chan::{Ch16, Ch32};
+
+    fn sine<Chan: Channel>(hz: u32) -> Audio<Chan, 2> {
+        let frames = (0..101)
+            .map(|i| {
+                let x = (i as f32 * 0.3).sin();
+                Frame::<Ch32, 2>::new(Ch32::new(x), Ch32::new(-0.5 * x)).to()
+            })
+            .collect::<Vec<_>>();
+        Audio::with_frames(hz, frames)
+    }
+
+    #[test]
+    fn rate() {
+        assert_eq!(
+            encode_rate(44_100),
+            [0x40, 0x0E, 0xAC, 0x44, 0, 0, 0, 0, 0, 0]
+        );
+        for hz in [1, 8_000, 22_050, 44_100, 48_000, 192_000, u32::MAX] {
+            assert_eq!(decode_rate(&encode_rate(hz)), Ok(hz));
+        }
+    }
+
+    #[test]
+    fn aiff_roundtrip() {
+        let audio = sine::<Ch16>(22_050);
+        let bytes = audio.save_aiff(16);
+        let loaded = Audio::<Ch16, 2>::load_aiff(&bytes).unwrap();
+        assert_eq!(loaded.sample_rate().get(), 22_050);
+        assert_eq!(loaded.as_slice(), audio.as_slice());
+
+        let audio = sine::<Ch24>(48_000);
+        let loaded = Audio::<Ch24, 2>::load_aiff(&audio.save_aiff(24));
+        assert_eq!(loaded.unwrap().as_slice(), audio.as_slice());
+
+        // 8-bit keeps the most significant byte of each sample.
+        let audio = sine::<Ch16>(8_000);
+        let bytes = audio.save_aiff(8);
+        let loaded = Audio::<Ch16, 2>::load_aiff(&bytes).unwrap();
+        for (a, b) in loaded.iter().zip(audio.iter()) {
+            for (a, b) in a.channels().iter().zip(b.channels()) {
+                assert_eq!(i16::from(*a) >> 8, i16::from(*b) >> 8);
+            }
+        }
+    }
+
+    #[test]
+    fn aiff_invalid() {
+        let bytes = sine::<Ch16>(8_000).save_aiff(16);
+        assert_eq!(
+            Audio::<Ch16, 1>::load_aiff(&bytes).unwrap_err(),
+            AudioError::Malformed("channel count doesn't match")
+        );
+        assert_eq!(
+            Audio::<Ch16, 2>::load_aiff(&bytes[..bytes.len() - 2]).unwrap_err(),
+            AudioError::Malformed("truncated chunk")
+        );
+        assert!(Audio::<Ch16, 2>::load_aiff(b"RIFF").is_err());
+    }
+}
@@ -0,0 +1,534 @@
+// Copyright © 2020-2022 The Fon Contributors.
+//
+// Licensed under any of:
+// - Apache License, Version 2.0 (https://www.apache.org/licenses/LICENSE-2.0)
+// - MIT License (https://mit-license.org/)
+// - Boost Software License, Version 1.0 (https://www.boost.org/LICENSE_1_0.txt)
+// At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
+// LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
+
+use_libm!();
+
+use crate::chan::{Ch32, Ch64, Channel};
+use crate::{Audio, Frame, StatsAccumulator};
+
+use alloc::{vec, vec::Vec};
+use core::f32::consts::{FRAC_PI_2, LN_10, PI};
+use core::ops::Range;
+
+/// Lowest pitch detected by `estimate_pitch()`, in hertz.
+const PITCH_MIN_HZ: f32 = 50.0;
+/// Highest pitch detected by `estimate_pitch()`, in hertz.
+const PITCH_MAX_HZ: f32 = 2_000.0;
+/// Minimum normalized autocorrelation for input to count as pitched.
+const PITCH_CONFIDENCE: f32 = 0.8;
+
+impl<const CH: usize> Audio<Ch32, CH> {
+    /// Count the samples that are out of range (absolute value greater than
+    /// 1.0), and would clip when converted to an integer format.
+    pub fn clipping_samples(&self) -> usize {
+        clipping_samples(self)
+    }
+
+    /// Get how far the loudest sample overshoots full scale, in decibels.
+    ///
+    /// Returns 0.0 if no samples are out of range.
+    pub fn max_overshoot_db(&self) -> f32 {
+        max_overshoot_db(self)
+    }
+}
+
+impl<const CH: usize> Audio<Ch64, CH> {
+    /// Count the samples that are out of range (absolute value greater than
+    /// 1.0), and would clip when converted to an integer format.
+    pub fn clipping_samples(&self) -> usize {
+        clipping_samples(self)
+    }
+
+    /// Get how far the loudest sample overshoots full scale, in decibels.
+    ///
+    /// Returns 0.0 if no samples are out of range.
+    pub fn max_overshoot_db(&self) -> f32 {
+        max_overshoot_db(self)
+    }
+}
+
+impl<Chan: Channel, const CH: usize> Audio<Chan, CH> {
+    /// Find the delay of channel `b` relative to channel `a` in frames, as the
+    /// lag (within ±`max_lag`) that maximizes their cross-correlation.
+    ///
+    /// A positive lag means `b` is late: shifting `b` earlier by the lag
+    /// aligns it with `a`.
+    ///
+    /// # Panics
+    /// If `a` or `b` is not a channel index.
+    pub fn channel_delay(&self, a: usize, b: usize, max_lag: usize) -> isize {
+        assert!(a < CH && b < CH);
+        let max_lag = max_lag.min(self.len().saturating_sub(1)) as isize;
+        let sample = |i: usize, chan: usize| {
+            f64::from(self.frames[i].channels()[chan].to_f32())
+        };
+        let mut best = (0, f64::NEG_INFINITY);
+        for lag in -max_lag..=max_lag {
+            let (start, end) = if lag < 0 {
+                ((-lag) as usize, self.len())
+            } else {
+                (0, self.len() - lag as usize)
+            };
+            let correlation: f64 = (start..end)
+                .map(|i| sample(i, a) * sample((i as isize + lag) as usize, b))
+                .sum();
+            if correlation > best.1 {
+                best = (lag, correlation);
+            }
+        }
+        best.0
+    }
+
+    /// Find the frame nearest to `index` where channel `chan` crosses zero
+    /// (`MID`), for snapping loop points and edits to reduce clicks.
+    ///
+    /// Of the two frames on either side of a crossing, the one closer to zero
+    /// is returned.  If the channel never crosses zero, returns `index`.
+    ///
+    /// # Panics
+    /// If `chan` is not a channel index.
+    pub fn nearest_zero_crossing(&self, chan: usize, index: usize) -> usize {
+        assert!(chan < CH);
+        let negative = |i: usize| self.frames[i].channels()[chan] < Chan::MID;
+        let level = |i: usize| self.frames[i].channels()[chan].to_f32().abs();
+        let distance = |i: usize| (i as isize - index as isize).abs();
+        let mut nearest = None;
+        for i in 1..self.len() {
+            if negative(i - 1) == negative(i) {
+                continue;
+            }
+            let crossing = if level(i - 1) < level(i) { i - 1 } else { i };
+            match nearest {
+                Some(n) if distance(n) <= distance(crossing) => {}
+                _ => nearest = Some(crossing),
+            }
+        }
+        nearest.unwrap_or(index)
+    }
+
+    /// Count how many samples (across all channels) fall into each of `bins`
+    /// equal-width amplitude bins from -1 to 1.
+    ///
+    /// Samples outside of -1 to 1 are counted in the first or last bin.
+    ///
+    /// # Panics
+    /// If `bins` is zero.
+    pub fn amplitude_histogram(&self, bins: usize) -> Vec<u64> {
+        assert_ne!(bins, 0);
+        let mut histogram = vec![0; bins];
+        for frame in self.iter() {
+            for chan in frame.channels() {
+                let x = (chan.to_f32() + 1.0) * 0.5 * bins as f32;
+                histogram[(x as usize).min(bins - 1)] += 1;
+            }
+        }
+        histogram
+    }
+
+    /// Get the minimum and maximum of each channel within each of `buckets`
+    /// equal spans of the audio, as for drawing a waveform overview.
+    ///
+    /// If there are fewer frames than buckets, empty spans are all zero.
+    ///
+    /// # Panics
+    /// If `buckets` is zero.
+    pub fn minmax_buckets(
+        &self,
+        buckets: usize,
+    ) -> Vec<([f32; CH], [f32; CH])> {
+        assert_ne!(buckets, 0);
+        (0..buckets)
+            .map(|bucket| {
+                let start = bucket * self.len() / buckets;
+                let end = (bucket + 1) * self.len() / buckets;
+                if start == end {
+                    return ([0.0; CH], [0.0; CH]);
+                }
+                let mut min = [f32::INFINITY; CH];
+                let mut max = [f32::NEG_INFINITY; CH];
+                for frame in &self.as_slice()[start..end] {
+                    for (chan, x) in frame.channels().iter().enumerate() {
+                        min[chan] = min[chan].min(x.to_f32());
+                        max[chan] = max[chan].max(x.to_f32());
+                    }
+                }
+                (min, max)
+            })
+            .collect()
+    }
+
+    /// Find the runs of at least `min_len_frames` frames where every channel
+    /// is quieter than `threshold_db`, for splitting recordings on silence.
+    pub fn silence_runs(
+        &self,
+        threshold_db: f32,
+        min_len_frames: usize,
+    ) -> Vec<Range<usize>> {
+        let threshold = (threshold_db / 20.0 * LN_10).exp();
+        let silent = |frame: &Frame<Chan, CH>| {
+            frame
+                .channels()
+                .iter()
+                .all(|x| x.to_f32().abs() < threshold)
+        };
+        let mut runs = Vec::new();
+        let mut start = None;
+        for (i, frame) in self.iter().enumerate() {
+            match (silent(frame), start) {
+                (true, None) => start = Some(i),
+                (false, Some(s)) => {
+                    if i - s >= min_len_frames {
+                        runs.push(s..i);
+                    }
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        if let Some(s) = start {
+            if self.len() - s >= min_len_frames {
+                runs.push(s..self.len());
+            }
+        }
+        runs
+    }
+
+    /// Calculate the crest factor (ratio of peak to RMS level, across all
+    /// channels), which is lower for more compressed audio.
+    ///
+    /// A full scale sine wave has a crest factor of √2, and a square wave 1.
+    /// Silent audio also returns 1.
+    pub fn crest_factor(&self) -> f32 {
+        let mut stats = StatsAccumulator::new();
+        stats.push_audio(self);
+        let stats = stats.finish();
+        let peak = stats.peak.iter().cloned().fold(0.0, f32::max);
+        let mean_sq = stats.rms.iter().map(|rms| rms * rms).sum::<f32>();
+        let rms = (mean_sq / CH as f32).sqrt();
+        if rms == 0.0 {
+            1.0
+        } else {
+            peak / rms
+        }
+    }
+
+    /// Calculate the [crest factor](Self::crest_factor) in decibels.
+    pub fn crest_factor_db(&self) -> f32 {
+        20.0 * self.crest_factor().log10()
+    }
+}
+
+impl<Chan: Channel> Audio<Chan, 1> {
+    /// Estimate the fundamental frequency of the audio in hertz, using
+    /// autocorrelation.
+    ///
+    /// Detects pitches from 50 Hz to 2 kHz.  Returns `None` if the audio is
+    /// too short, or is unvoiced / too noisy to have a clear pitch.
+    pub fn estimate_pitch(&self) -> Option<f32> {
+        let hz = self.sample_rate().get() as f32;
+        let samples: Vec<f32> =
+            self.iter().map(|f| f.channels()[0].to_f32()).collect();
+        let min_lag = ((hz / PITCH_MAX_HZ) as usize).max(1);
+        let max_lag = ((hz / PITCH_MIN_HZ) as usize).min(samples.len() / 2);
+        if min_lag + 2 > max_lag {
+            return None;
+        }
+
+        // Normalized autocorrelation for each candidate period.
+        let corr: Vec<f32> = (min_lag - 1..=max_lag + 1)
+            .map(|lag| {
+                let (a, b) = (&samples[..samples.len() - lag], &samples[lag..]);
+                let dot: f32 = a.iter().zip(b).map(|(a, b)| a * b).sum();
+                let a: f32 = a.iter().map(|a| a * a).sum();
+                let b: f32 = b.iter().map(|b| b * b).sum();
+                if a == 0.0 || b == 0.0 {
+                    0.0
+                } else {
+                    dot / (a * b).sqrt()
+                }
+            })
+            .collect();
+        let best = corr[1..corr.len() - 1].iter().cloned().fold(0.0, f32::max);
+        if best < PITCH_CONFIDENCE {
+            return None;
+        }
+
+        // Take the first peak close to the best, to avoid octave errors.
+        let i = (1..corr.len() - 1).find(|&i| {
+            corr[i] >= 0.95 * best
+                && corr[i] >= corr[i - 1]
+                && corr[i] >= corr[i + 1]
+        })?;
+        // Parabolic interpolation for a fractional period.
+        let (l, c, r) = (corr[i - 1], corr[i], corr[i + 1]);
+        let denom = l - 2.0 * c + r;
+        let shift = if denom == 0.0 {
+            0.0
+        } else {
+            0.5 * (l - r) / denom
+        };
+        let period = (min_lag - 1 + i) as f32 + shift;
+        Some(hz / period)
+    }
+
+    /// Get the amplitude envelope of the audio, by full-wave rectifying and
+    /// then lowpassing at `smoothing_hz` (like an analog envelope follower).
+    ///
+    /// The envelope is scaled to track the peak amplitude of a sine wave.
+    /// `smoothing_hz` should be well below the frequencies in the audio, or
+    /// the envelope will ripple.
+    pub fn envelope_rectified(&self, smoothing_hz: f32) -> Audio<Ch32, 1> {
+        let hz = self.sample_rate().get() as f32;
+        let a = (-2.0 * PI * smoothing_hz / hz).exp();
+        // Two one-pole lowpasses in series, for a steeper rolloff.
+        let (mut y1, mut y2) = (0.0, 0.0);
+        let frames = self
+            .iter()
+            .map(|frame| {
+                let x = frame.channels()[0].to_f32().abs();
+                y1 = (1.0 - a) * x + a * y1;
+                y2 = (1.0 - a) * y1 + a * y2;
+                // The mean of a rectified sine is 2/π of its peak.
+                Frame::<Ch32, 1>::new(Ch32::new(y2 * FRAC_PI_2))
+            })
+            .collect::<Vec<_>>();
+        Audio::with_frames(self.sample_rate().get(), frames)
+    }
+}
+
+fn clipping_samples<Chan: Channel, const CH: usize>(
+    audio: &Audio<Chan, CH>,
+) -> usize {
+    audio
+        .iter()
+        .flat_map(|frame| frame.channels().iter())
+        .filter(|chan| chan.to_f32().abs() > 1.0)
+        .count()
+}
+
+fn max_overshoot_db<Chan: Channel, const CH: usize>(
+    audio: &Audio<Chan, CH>,
+) -> f32 {
+    let peak = audio
+        .iter()
+        .flat_map(|frame| frame.channels().iter())
+        .map(|chan| chan.to_f32().abs())
+        .fold(1.0, f32::max);
+    20.0 * peak.log10()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chan::{Ch32, Channel};
+    use crate::{Audio, Frame};
+    use alloc::{vec, vec::Vec};
+
+    #[test]
+    fn clipping() {
+        let audio = Audio::<Ch32, 2>::with_f32_buffer(
+            48_000,
+            vec![0.5, -1.0, 1.25, -1.5, 1.0, 0.0],
+        );
+        assert_eq!(audio.clipping_samples(), 2);
+        assert!((audio.max_overshoot_db() - 3.5218).abs() < 0.001);
+
+        let audio =
+            Audio::<Ch32, 1>::with_f32_buffer(48_000, vec![0.5, -1.0, 1.0]);
+        assert_eq!(audio.clipping_samples(), 0);
+        assert_eq!(audio.max_overshoot_db(), 0.0);
+    }
+
+    #[test]
+    fn channel_delay() {
+        // Pseudo-random noise, so there's a single clear peak.
+        let mut seed = 1u32;
+        let noise = (0..1000)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                (seed >> 8) as f32 / (1 << 23) as f32 - 1.0
+            })
+            .collect::<Vec<_>>();
+        let frames = (0..1000)
+            .map(|i| {
+                let right = if i >= 17 { noise[i - 17] } else { 0.0 };
+                Frame::<Ch32, 2>::new(Ch32::new(noise[i]), Ch32::new(right))
+            })
+            .collect::<Vec<_>>();
+        let audio = Audio::with_frames(48_000, frames);
+        assert_eq!(audio.channel_delay(0, 1, 50), 17);
+        assert_eq!(audio.channel_delay(1, 0, 50), -17);
+        assert_eq!(audio.channel_delay(0, 0, 50), 0);
+    }
+
+    #[test]
+    fn nearest_zero_crossing() {
+        // 100 frame period, offset so no frame is exactly zero.
+        let step = 2.0 * core::f32::consts::PI / 100.0;
+        let frames = (0..1000)
+            .map(|i| {
+                let x = ((i as f32 + 0.3) * step).sin();
+                Frame::<Ch32, 2>::new(Ch32::new(x), Ch32::new(0.5))
+            })
+            .collect::<Vec<_>>();
+        let audio = Audio::with_frames(48_000, frames);
+        for index in [0, 37, 260, 999] {
+            let found = audio.nearest_zero_crossing(0, index);
+            assert!((found as isize - index as isize).abs() <= 50);
+            let sample = audio.get(found).unwrap().channels()[0].to_f32();
+            assert!(sample.abs() < step);
+        }
+        assert_eq!(audio.nearest_zero_crossing(0, 260), 250);
+        assert_eq!(audio.nearest_zero_crossing(1, 260), 260);
+    }
+
+    #[test]
+    fn amplitude_histogram() {
+        let audio = Audio::<Ch32, 2>::with_f32_buffer(48_000, vec![0.3; 200]);
+        let histogram = audio.amplitude_histogram(10);
+        assert_eq!(histogram, [0, 0, 0, 0, 0, 0, 200, 0, 0, 0]);
+
+        let audio =
+            Audio::<Ch32, 1>::with_f32_buffer(48_000, vec![-1.0, 1.0, 2.0]);
+        assert_eq!(audio.amplitude_histogram(4), [1, 0, 0, 2]);
+
+        // White noise spreads evenly.
+        let mut seed = 7u32;
+        let noise = (0..10_000)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                (seed >> 8) as f32 / (1 << 23) as f32 - 1.0
+            })
+            .collect::<Vec<_>>();
+        let audio = Audio::<Ch32, 1>::with_f32_buffer(48_000, noise);
+        let histogram = audio.amplitude_histogram(10);
+        assert_eq!(histogram.iter().sum::<u64>(), 10_000);
+        for count in histogram {
+            assert!((800..1200).contains(&count), "{}", count);
+        }
+    }
+
+    #[test]
+    fn minmax_buckets() {
+        let ramp = (0..100)
+            .flat_map(|i| [i as f32 / 100.0, -(i as f32) / 100.0])
+            .collect::<Vec<_>>();
+        let audio = Audio::<Ch32, 2>::with_f32_buffer(48_000, ramp);
+        let buckets = audio.minmax_buckets(8);
+        assert_eq!(buckets.len(), 8);
+        for (i, (min, max)) in buckets.iter().enumerate() {
+            let start = i * 100 / 8;
+            let end = (i + 1) * 100 / 8;
+            assert_eq!(min[0], start as f32 / 100.0);
+            assert_eq!(max[0], (end - 1) as f32 / 100.0);
+            assert_eq!(min[1], -max[0]);
+            assert_eq!(max[1], -min[0]);
+            for frame in &audio.as_slice()[start..end] {
+                for (chan, x) in frame.channels().iter().enumerate() {
+                    assert!(min[chan] <= x.to_f32() && x.to_f32() <= max[chan]);
+                }
+            }
+        }
+
+        let audio = Audio::<Ch32, 1>::with_f32_buffer(48_000, vec![0.5]);
+        assert_eq!(audio.minmax_buckets(2), [([0.0], [0.0]), ([0.5], [0.5])]);
+    }
+
+    #[test]
+    fn crest_factor() {
+        let step = 2.0 * core::f32::consts::PI / 100.0;
+        let frames = (0..4800)
+            .map(|i| {
+                let x = (i as f32 * step).sin();
+                let square = if x < 0.0 { -0.5 } else { 0.5 };
+                Frame::<Ch32, 2>::new(Ch32::new(x), Ch32::new(square))
+            })
+            .collect::<Vec<_>>();
+        let audio = Audio::with_frames(48_000, frames);
+        let (sine, square) = audio.split();
+        assert!((sine.crest_factor() - 2.0f32.sqrt()).abs() < 0.001);
+        assert!((sine.crest_factor_db() - 3.0103).abs() < 0.01);
+        assert!((square.crest_factor() - 1.0).abs() < 0.001);
+        assert!(square.crest_factor_db().abs() < 0.01);
+
+        let silence = Audio::<Ch32, 2>::with_silence(48_000, 100);
+        assert_eq!(silence.crest_factor_db(), 0.0);
+    }
+
+    #[test]
+    fn silence_runs() {
+        let tone =
+            |len: usize| (0..len).map(|i| if i % 2 == 0 { 0.5 } else { -0.5 });
+        let samples = tone(100)
+            .chain(core::iter::repeat(0.0001).take(300))
+            .chain(tone(100))
+            .chain(core::iter::repeat(0.0).take(20))
+            .collect::<Vec<f32>>();
+        let audio = Audio::<Ch32, 1>::with_f32_buffer(48_000, samples);
+        assert_eq!(audio.silence_runs(-60.0, 50), vec![100..400]);
+        assert_eq!(audio.silence_runs(-60.0, 10), vec![100..400, 500..520]);
+        assert_eq!(audio.silence_runs(-90.0, 10), vec![500..520]);
+    }
+
+    #[test]
+    fn estimate_pitch() {
+        let step = 2.0 * core::f32::consts::PI * 440.0 / 48_000.0;
+        let frames = (0..4800)
+            .map(|i| Frame::<Ch32, 1>::new(Ch32::new((i as f32 * step).sin())))
+            .collect::<Vec<_>>();
+        let audio = Audio::with_frames(48_000, frames);
+        let pitch = audio.estimate_pitch().unwrap();
+        assert!((pitch - 440.0).abs() < 1.0, "{}", pitch);
+
+        // White noise has no pitch.
+        let mut seed = 0x1234_5678u32;
+        let frames = (0..4800)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                let x = (seed >> 8) as f32 / (1 << 23) as f32 - 1.0;
+                Frame::<Ch32, 1>::new(Ch32::new(x))
+            })
+            .collect::<Vec<_>>();
+        let audio = Audio::with_frames(48_000, frames);
+        assert_eq!(audio.estimate_pitch(), None);
+
+        // Silence has no pitch.
+        let audio = Audio::<Ch32, 1>::with_silence(48_000, 4800);
+        assert_eq!(audio.estimate_pitch(), None);
+    }
+
+    #[test]
+    fn envelope_rectified() {
+        // A 1 kHz tone, amplitude modulated at 5 Hz between 0.1 and 0.9.
+        let (tau, hz) = (2.0 * core::f32::consts::PI, 48_000.0);
+        let gain = |i: usize| 0.5 + 0.4 * (tau * 5.0 * i as f32 / hz).sin();
+        let frames = (0..48_000)
+            .map(|i| {
+                let x = gain(i) * (tau * 1_000.0 * i as f32 / hz).sin();
+                Frame::<Ch32, 1>::new(Ch32::new(x))
+            })
+            .collect::<Vec<_>>();
+        let audio = Audio::with_frames(48_000, frames);
+        let envelope = audio.envelope_rectified(50.0);
+        assert_eq!(envelope.len(), audio.len());
+
+        // After settling, the envelope follows the modulation.
+        let samples = envelope
+            .iter()
+            .skip(4_800)
+            .map(|frame| frame.channels()[0].to_f32())
+            .collect::<Vec<_>>();
+        let max = samples.iter().cloned().fold(0.0, f32::max);
+        let min = samples.iter().cloned().fold(1.0, f32::min);
+        assert!((max - 0.9).abs() < 0.1, "{}", max);
+        assert!((min - 0.1).abs() < 0.1, "{}", min);
+        let peak = samples.iter().position(|&x| x == max).unwrap() + 4_800;
+        let phase = (peak % 9_600) as f32 / 9_600.0;
+        assert!(phase > 0.25 && phase < 0.4, "{}", phase);
+    }
+}
@@ -0,0 +1,72 @@
+// Copyright © 2020-2022 The Fon Contributors.
+//
+// Licensed under any of:
+// - Apache License, Version 2.0 (https://www.apache.org/licenses/LICENSE-2.0)
+// - MIT License (https://mit-license.org/)
+// - Boost Software License, Version 1.0 (https://www.boost.org/LICENSE_1_0.txt)
+// At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
+// LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
+
+use crate::chan::Channel;
+use crate::frame::Frame;
+use crate::Audio;
+
+use alloc::vec::Vec;
+use ndarray::Array2;
+
+impl<Chan: Channel, const CH: usize> Audio<Chan, CH> {
+    /// Copy the audio into a 2-dimensional `f32` array with the shape
+    /// `[frames, channels]` (requires the `ndarray` feature).
+    pub fn to_array2(&self) -> Array2<f32> {
+        Array2::from_shape_fn((self.len(), CH), |(frame, chan)| {
+            self.frames[frame].channels()[chan].to_f32()
+        })
+    }
+
+    /// Construct an `Audio` buffer from a 2-dimensional `f32` array with the
+    /// shape `[frames, channels]` (requires the `ndarray` feature).
+    ///
+    /// # Panics
+    /// If the number of columns in the array doesn't match the channel count.
+    pub fn from_array2(hz: u32, array: &Array2<f32>) -> Self {
+        assert_eq!(array.ncols(), CH);
+        let frames = array
+            .rows()
+            .into_iter()
+            .map(|row| {
+                let mut frame = Frame::<Chan, CH>::default();
+                for (chan, sample) in frame.channels_mut().iter_mut().zip(row) {
+                    *chan = Chan::from(*sample);
+                }
+                frame
+            })
+            .collect::<Vec<_>>();
+        Self::with_frames(hz, frames)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chan::Ch32;
+    use crate::{Audio, Frame};
+    use alloc::vec::Vec;
+
+    #[test]
+    fn array2_roundtrip() {
+        let frames = (0..16)
+            .map(|i| {
+                let i = i as f32 / 16.0;
+                Frame::<Ch32, 2>::new(Ch32::new(i), Ch32::new(-i))
+            })
+            .collect::<Vec<_>>();
+        let audio = Audio::with_frames(44_100, frames);
+        let array = audio.to_array2();
+        assert_eq!(array.shape(), &[16, 2]);
+        assert_eq!(array[[4, 0]], 0.25);
+        assert_eq!(array[[4, 1]], -0.25);
+
+        let back = Audio::<Ch32, 2>::from_array2(44_100, &array);
+        assert_eq!(back.len(), audio.len());
+        assert_eq!(back.as_slice(), audio.as_slice());
+    }
+}
@@ -0,0 +1,208 @@
+// Copyright © 2020-2022 The Fon Contributors.
+//
+// Licensed under any of:
+// - Apache License, Version 2.0 (https://www.apache.org/licenses/LICENSE-2.0)
+// - MIT License (https://mit-license.org/)
+// - Boost Software License, Version 1.0 (https://www.boost.org/LICENSE_1_0.txt)
+// At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
+// LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
+
+use_libm!();
+
+use crate::chan::Channel;
+use crate::frame::Frame;
+use crate::Audio;
+
+use alloc::{vec, vec::Vec};
+use core::f32::consts::PI;
+
+/// Complex number for the FFT.
+#[derive(Copy, Clone, Debug, Default)]
+struct Complex {
+    re: f32,
+    im: f32,
+}
+
+impl Complex {
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            re: self.re + rhs.re,
+            im: self.im + rhs.im,
+        }
+    }
+
+    fn sub(self, rhs: Self) -> Self {
+        Self {
+            re: self.re - rhs.re,
+            im: self.im - rhs.im,
+        }
+    }
+
+    fn mul(self, rhs: Self) -> Self {
+        Self {
+            re: self.re * rhs.re - self.im * rhs.im,
+            im: self.re * rhs.im + self.im * rhs.re,
+        }
+    }
+}
+
+/// Radix-2 FFT of a power of two size, with precomputed twiddle factors.
+struct Fft {
+    twiddles: Vec<Complex>,
+}
+
+impl Fft {
+    fn new(len: usize) -> Self {
+        debug_assert!(len.is_power_of_two());
+        let twiddles = (0..len / 2)
+            .map(|k| {
+                let angle = -2.0 * PI * k as f32 / len as f32;
+                Complex {
+                    re: angle.cos(),
+                    im: angle.sin(),
+                }
+            })
+            .collect();
+        Self { twiddles }
+    }
+
+    /// Transform `buf` in place (unscaled when `inverse`).
+    fn process(&self, buf: &mut [Complex], inverse: bool) {
+        let len = buf.len();
+
+        // Bit reversal permutation.
+        let bits = len.trailing_zeros();
+        for i in 0..len {
+            let j = i.reverse_bits() >> (usize::BITS - bits);
+            if i < j {
+                buf.swap(i, j);
+            }
+        }
+
+        // Butterflies.
+        let mut size = 2;
+        while size <= len {
+            let stride = len / size;
+            for start in (0..len).step_by(size) {
+                for k in 0..size / 2 {
+                    let mut twiddle = self.twiddles[k * stride];
+                    if inverse {
+                        twiddle.im = -twiddle.im;
+                    }
+                    let a = buf[start + k];
+                    let b = buf[start + k + size / 2].mul(twiddle);
+                    buf[start + k] = a.add(b);
+                    buf[start + k + size / 2] = a.sub(b);
+                }
+            }
+            size *= 2;
+        }
+    }
+}
+
+impl<Chan: Channel, const CH: usize> Audio<Chan, CH> {
+    /// Convolve each channel with an impulse response, as for convolution
+    /// reverb or speaker cabinet simulation (requires the `fft` feature).
+    ///
+    /// Uses overlap-add FFT convolution, so multi-second impulse responses
+    /// are practical.  The result is `ir.len() - 1` frames longer, to hold the
+    /// tail.
+    ///
+    /// # Panics
+    /// If the sample rates don't match.
+    pub fn convolve_ir(&self, ir: &Audio<Chan, 1>) -> Audio<Chan, CH> {
+        assert_eq!(self.sample_rate(), ir.sample_rate());
+        if self.is_empty() || ir.is_empty() {
+            return Audio::with_frames(self.sample_rate().get(), Vec::new());
+        }
+
+        // Blocks of input as long as the impulse response, zero-padded so the
+        // circular convolution doesn't wrap around.
+        let block = ir.len().next_power_of_two();
+        let size = block * 2;
+        let fft = Fft::new(size);
+        let mut response = vec![Complex::default(); size];
+        for (x, frame) in response.iter_mut().zip(ir.iter()) {
+            x.re = frame.channels()[0].to_f32();
+        }
+        fft.process(&mut response, false);
+
+        let len = self.len() + ir.len() - 1;
+        let mut out = vec![[0.0f32; CH]; len];
+        let mut buf = vec![Complex::default(); size];
+        for chan in 0..CH {
+            for (i, frames) in self.as_slice().chunks(block).enumerate() {
+                for x in buf.iter_mut() {
+                    *x = Complex::default();
+                }
+                for (x, frame) in buf.iter_mut().zip(frames) {
+                    x.re = frame.channels()[chan].to_f32();
+                }
+                fft.process(&mut buf, false);
+                for (x, h) in buf.iter_mut().zip(response.iter()) {
+                    *x = x.mul(*h);
+                }
+                fft.process(&mut buf, true);
+                let offset = i * block;
+                for (out, x) in out[offset..].iter_mut().zip(buf.iter()) {
+                    out[chan] += x.re / size as f32;
+                }
+            }
+        }
+
+        let frames = out
+            .iter()
+            .map(|samples| {
+                let mut frame = Frame::<Chan, CH>::default();
+                for (chan, x) in frame.channels_mut().iter_mut().zip(samples) {
+                    *chan = Chan::from(*x);
+                }
+                frame
+            })
+            .collect::<Vec<_>>();
+        Audio::with_frames(self.sample_rate().get(), frames)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chan::{Ch32, Channel};
+    use crate::{Audio, Frame};
+    use alloc::vec::Vec;
+
+    fn tone() -> Audio<Ch32, 2> {
+        let frames = (0..5000)
+            .map(|i| {
+                let x = (i as f32 * 0.01).sin();
+                Frame::<Ch32, 2>::new(Ch32::new(x), Ch32::new(0.5 * x))
+            })
+            .collect::<Vec<_>>();
+        Audio::with_frames(1_000, frames)
+    }
+
+    fn assert_close(a: &Audio<Ch32, 2>, b: &Audio<Ch32, 2>) {
+        assert_eq!(a.len(), b.len());
+        for (a, b) in a.iter().zip(b.iter()) {
+            for (a, b) in a.channels().iter().zip(b.channels()) {
+                assert!((a.to_f32() - b.to_f32()).abs() < 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn convolve_ir() {
+        // A unit impulse returns the input.
+        let audio = tone();
+        let unit = Audio::<Ch32, 1>::with_f32_buffer(1_000, [1.0]);
+        assert_close(&audio.convolve_ir(&unit), &audio);
+
+        // A single echo 10 ms (10 frames) later matches `echo()`.
+        let mut ir = [0.0; 11];
+        ir[0] = 1.0;
+        ir[10] = 0.5;
+        let ir = Audio::<Ch32, 1>::with_f32_buffer(1_000, ir);
+        let mut echo = tone();
+        echo.echo(10.0, 0.0, 0.5);
+        assert_close(&audio.convolve_ir(&ir), &echo);
+    }
+}
@@ -0,0 +1,1140 @@
+// Copyright © 2020-2022 The Fon Contributors.
+//
+// Licensed under any of:
+// - Apache License, Version 2.0 (https://www.apache.org/licenses/LICENSE-2.0)
+// - MIT License (https://mit-license.org/)
+// - Boost Software License, Version 1.0 (https://www.boost.org/LICENSE_1_0.txt)
+// At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
+// LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
+
+use_libm!();
+
+use crate::chan::{Ch32, Channel};
+use crate::frame::Frame;
+use crate::math::smoothing;
+use crate::stream::sinc;
+use crate::{Audio, FirFilter, Lfo, Window};
+
+use alloc::{vec, vec::Vec};
+
+/// Level (relative to the first repeat) below which echo repeats are dropped.
+const ECHO_FLOOR: f32 = 0.001;
+/// Approximate radius of a human head (in meters).
+const HEAD_RADIUS: f32 = 0.0875;
+/// Speed of sound in air (in meters per second).
+const SPEED_OF_SOUND: f32 = 343.0;
+/// Level difference between the ears for a source directly to one side.
+const MAX_ILD_DB: f32 = 10.0;
+/// Cutoff frequency of the head shadow filter (in hertz).
+const HEAD_SHADOW_HZ: f32 = 1500.0;
+/// Length of the windowed sinc filter used for fractional delays.
+const FRACTIONAL_DELAY_TAPS: i32 = 32;
+
+/// Get the gain reduction (in decibels) of a compressor for a level
+/// `over_db` above the threshold, where `slope` is `1 - 1 / ratio`.
+///
+/// Within the soft knee (`knee_db` wide, centered on the threshold) the slope
+/// eases in quadratically, meeting the straight lines on either side.
+fn gain_reduction_db(over_db: f32, slope: f32, knee_db: f32) -> f32 {
+    if 2.0 * over_db.abs() < knee_db {
+        let x = over_db + knee_db / 2.0;
+        slope * x * x / (2.0 * knee_db)
+    } else if over_db > 0.0 {
+        over_db * slope
+    } else {
+        0.0
+    }
+}
+
+/// Expander, which increases dynamic range (the opposite of a compressor),
+/// applied with [`Audio::expand()`].
+///
+/// A downward expander turns audio under the threshold down further, so each
+/// decibel below it becomes `ratio` decibels; with an infinite ratio it's a
+/// noise gate.  An upward expander instead turns audio over the threshold up
+/// further.  The level is tracked by a peak envelope across all channels,
+/// which rises over the attack time and falls over the release time, and
+/// carries over between buffers.
+#[derive(Copy, Clone, Debug)]
+pub struct Expander {
+    threshold_db: f32,
+    ratio: f32,
+    attack_ms: f32,
+    release_ms: f32,
+    upward: bool,
+    envelope: f32,
+}
+
+impl Expander {
+    /// Create a downward expander, turning audio under `threshold_db` down.
+    ///
+    /// # Panics
+    /// If `ratio` is less than 1.
+    pub fn new(
+        threshold_db: f32,
+        ratio: f32,
+        attack_ms: f32,
+        release_ms: f32,
+    ) -> Self {
+        assert!(ratio >= 1.0);
+        Self {
+            threshold_db,
+            ratio,
+            attack_ms,
+            release_ms,
+            upward: false,
+            envelope: 0.0,
+        }
+    }
+
+    /// Create a noise gate (a downward expander with an infinite ratio),
+    /// silencing audio under `threshold_db`.
+    pub fn gate(threshold_db: f32, attack_ms: f32, release_ms: f32) -> Self {
+        Self::new(threshold_db, f32::INFINITY, attack_ms, release_ms)
+    }
+
+    /// Create an upward expander, turning audio over `threshold_db` up.  This
+    /// may clip, so leave headroom.
+    ///
+    /// # Panics
+    /// If `ratio` is less than 1.
+    pub fn upward(
+        threshold_db: f32,
+        ratio: f32,
+        attack_ms: f32,
+        release_ms: f32,
+    ) -> Self {
+        let mut expander =
+            Self::new(threshold_db, ratio, attack_ms, release_ms);
+        expander.upward = true;
+        expander
+    }
+
+    /// Get the gain for the next frame, with a peak `level`.
+    fn gain(&mut self, level: f32, attack: f32, release: f32) -> f32 {
+        let coefficient = if level > self.envelope {
+            attack
+        } else {
+            release
+        };
+        self.envelope =
+            coefficient * self.envelope + (1.0 - coefficient) * level;
+
+        // Kept finite in silence, so a ratio of 1 doesn't multiply infinity
+        // by 0.
+        let over_db =
+            (20.0 * self.envelope.log10() - self.threshold_db).max(f32::MIN);
+        let change_db = match (self.upward, over_db) {
+            (false, over_db) if over_db < 0.0 => over_db * (self.ratio - 1.0),
+            (true, over_db) if over_db > 0.0 => over_db * (self.ratio - 1.0),
+            _ => 0.0,
+        };
+        (change_db / 20.0 * core::f32::consts::LN_10).exp()
+    }
+}
+
+impl<Chan: Channel, const CH: usize> Audio<Chan, CH> {
+    /// Normalize each channel independently, bringing every channel's peak to
+    /// full scale.
+    ///
+    /// Unlike a uniform gain, this intentionally alters the balance between
+    /// channels (and therefore the stereo image), which is useful for
+    /// correcting mismatched input gains.  Silent channels are left unchanged.
+    pub fn normalize_per_channel(&mut self) {
+        let mut peaks = [0.0f32; CH];
+        for frame in self.iter() {
+            for (peak, chan) in peaks.iter_mut().zip(frame.channels()) {
+                *peak = peak.max(chan.to_f32().abs());
+            }
+        }
+        let mut gains = [1.0f32; CH];
+        for (gain, peak) in gains.iter_mut().zip(peaks.iter()) {
+            if *peak > 0.0 {
+                *gain = peak.recip();
+            }
+        }
+        for frame in self.iter_mut() {
+            frame.gain_each(gains);
+        }
+    }
+
+    /// Apply per-channel calibration in one pass, scaling each channel by its
+    /// gain, and inverting the polarity of channels where `polarity` is true.
+    pub fn apply_calibration(
+        &mut self,
+        gains: [f32; CH],
+        polarity: [bool; CH],
+    ) {
+        let mut gains = gains;
+        for (gain, invert) in gains.iter_mut().zip(polarity.iter()) {
+            if *invert {
+                *gain = -*gain;
+            }
+        }
+        for frame in self.iter_mut() {
+            frame.gain_each(gains);
+        }
+    }
+
+    /// Add a feedback delay (echo) to the audio.
+    ///
+    /// Each repeat is `delay_ms` milliseconds after the previous one, and is
+    /// `feedback` times as loud; the first repeat is mixed in at a level of
+    /// `mix`.  The buffer is extended to hold the echo tail, which ends once
+    /// repeats decay below -60 dB.
+    ///
+    /// # Panics
+    /// If `feedback` is not in the range `0.0..1.0`.
+    pub fn echo(&mut self, delay_ms: f32, feedback: f32, mix: f32) {
+        assert!((0.0..1.0).contains(&feedback));
+        let hz = self.sample_rate().get() as f32;
+        let delay = ((delay_ms * hz / 1000.0).round() as usize).max(1);
+
+        // Count repeats audible above the floor.
+        let mut repeats = 1;
+        let mut level = 1.0;
+        while level * feedback >= ECHO_FLOOR {
+            level *= feedback;
+            repeats += 1;
+        }
+
+        let dry_len = self.len();
+        let len = dry_len + delay * repeats;
+        let mut wet = vec![[0.0f32; CH]; len];
+        let frames = &mut self.frames;
+        frames.resize(len, Frame::default());
+        for i in delay..len {
+            let dry = frames[i - delay];
+            for (chan, dry) in dry.channels().iter().enumerate() {
+                wet[i][chan] = dry.to_f32() + feedback * wet[i - delay][chan];
+            }
+        }
+        for (frame, wet) in frames.iter_mut().zip(wet.iter()) {
+            for (chan, wet) in frame.channels_mut().iter_mut().zip(wet) {
+                *chan = Chan::from(chan.to_f32() + mix * wet);
+            }
+        }
+    }
+
+    /// Filter each channel with a finite impulse response kernel of `taps`
+    /// (direct convolution), for custom EQ and correction curves.
+    ///
+    /// The length stays the same; use a [`FirFilter`] to continue filtering
+    /// across buffers.
+    ///
+    /// # Panics
+    /// If `taps` is empty.
+    pub fn convolve_fir(&mut self, taps: &[f32]) {
+        FirFilter::new(taps).process(self);
+    }
+
+    /// Ramp the gain from `from_db` at the first frame to `to_db` at the last,
+    /// interpolating in decibels for a fade that sounds even.
+    pub fn gain_ramp_db(&mut self, from_db: f32, to_db: f32) {
+        let steps = self.len().saturating_sub(1).max(1) as f32;
+        for (i, frame) in self.frames.iter_mut().enumerate() {
+            let db = from_db + (to_db - from_db) * i as f32 / steps;
+            frame.gain((db / 20.0 * core::f32::consts::LN_10).exp());
+        }
+    }
+
+    /// Silence every sample whose absolute value is below `threshold`.
+    ///
+    /// This is a cheap noise gate that works sample by sample, with no
+    /// envelope, so it also cuts the quiet parts of louder waveforms.
+    pub fn gate(&mut self, threshold: f32) {
+        for frame in self.frames.iter_mut() {
+            *frame = frame.gate(threshold);
+        }
+    }
+
+    /// Reconstruct clipped peaks, where runs of samples are pinned at or above
+    /// `threshold` (in absolute value), as for restoring an overdriven
+    /// recording.
+    ///
+    /// Each run is replaced by a cubic curve through the two unclipped samples
+    /// on either side of it, so the restored peaks go past `threshold`.
+    /// Integer channel types clip again at full scale, so declip a copy
+    /// converted to [`Ch32`] and turn it down for headroom.  Runs at the very
+    /// start or end of the audio are left alone.
+    pub fn declip(&mut self, threshold: f32) {
+        let len = self.len();
+        for chan in 0..CH {
+            let samples = self
+                .frames
+                .iter()
+                .map(|frame| frame.channels()[chan].to_f32())
+                .collect::<Vec<_>>();
+            let mut i = 0;
+            while i < len {
+                if samples[i].abs() < threshold {
+                    i += 1;
+                    continue;
+                }
+                let start = i;
+                while i < len && samples[i].abs() >= threshold {
+                    i += 1;
+                }
+                if start < 2 || i + 2 > len {
+                    continue;
+                }
+
+                // Lagrange interpolation through the neighboring samples,
+                // positioned relative to the start of the run.
+                let run = (i - start) as f32;
+                let xs = [-2.0, -1.0, run, run + 1.0];
+                let ys = [
+                    samples[start - 2],
+                    samples[start - 1],
+                    samples[i],
+                    samples[i + 1],
+                ];
+                for (j, frame) in self.frames[start..i].iter_mut().enumerate() {
+                    let x = j as f32;
+                    let mut y = 0.0;
+                    for (k, (xk, yk)) in xs.iter().zip(ys.iter()).enumerate() {
+                        let mut basis = 1.0;
+                        for (m, xm) in xs.iter().enumerate() {
+                            if m != k {
+                                basis *= (x - xm) / (xk - xm);
+                            }
+                        }
+                        y += yk * basis;
+                    }
+                    frame.channels_mut()[chan] = Chan::from(y);
+                }
+            }
+        }
+    }
+
+    /// Apply `gain` only to the frames where `mask` is true, leaving the rest
+    /// unchanged (for example, to duck only the regions found by
+    /// [`silence_runs()`](Self::silence_runs)).
+    ///
+    /// # Panics
+    /// If `mask` isn't the same length as the audio.
+    pub fn gain_masked(&mut self, mask: &[bool], gain: f32) {
+        assert_eq!(mask.len(), self.len());
+        for (frame, _) in self.frames.iter_mut().zip(mask).filter(|x| *x.1) {
+            frame.gain(gain);
+        }
+    }
+
+    /// Mix `sources` together into a new buffer, attenuating the mix just
+    /// enough that it doesn't clip.
+    ///
+    /// The mix is as long as the longest source.  Returns the mix, and the gain
+    /// that was applied to it (1.0 if no attenuation was needed).
+    ///
+    /// # Panics
+    /// If a source's sample rate doesn't match `hz`.
+    pub fn mix_auto(hz: u32, sources: &[&Self]) -> (Self, f32) {
+        let len = sources.iter().map(|src| src.len()).max().unwrap_or(0);
+        let mut sum = vec![[0.0f32; CH]; len];
+        for src in sources {
+            assert_eq!(src.sample_rate().get(), hz);
+            for (sum, frame) in sum.iter_mut().zip(src.iter()) {
+                for (sum, chan) in sum.iter_mut().zip(frame.channels()) {
+                    *sum += chan.to_f32();
+                }
+            }
+        }
+
+        let peak = sum.iter().flatten().fold(0.0f32, |p, s| p.max(s.abs()));
+        let gain = if peak > 1.0 { peak.recip() } else { 1.0 };
+        let frames = sum
+            .iter()
+            .map(|sum| {
+                let mut frame = Frame::<Chan, CH>::default();
+                for (chan, sum) in frame.channels_mut().iter_mut().zip(sum) {
+                    *chan = Chan::from(sum * gain);
+                }
+                frame
+            })
+            .collect::<Vec<_>>();
+        (Audio::with_frames(hz, frames), gain)
+    }
+
+    /// Mix `other` onto this audio, with a gain for each frame from
+    /// `gain_fn` (called with the frame index), as for a clip gain envelope
+    /// during mixdown.
+    ///
+    /// If `other` is longer, this audio is extended with silence to fit it.
+    ///
+    /// # Panics
+    /// If the sample rates don't match.
+    pub fn mix_automated<F: Fn(usize) -> f32>(
+        &mut self,
+        other: &Self,
+        gain_fn: F,
+    ) {
+        assert_eq!(self.sample_rate(), other.sample_rate());
+        if other.len() > self.len() {
+            self.frames.resize(other.len(), Frame::default());
+        }
+        for (i, (frame, src)) in
+            self.frames.iter_mut().zip(other.iter()).enumerate()
+        {
+            let gain = gain_fn(i);
+            for (chan, src) in
+                frame.channels_mut().iter_mut().zip(src.channels())
+            {
+                *chan = Chan::from(chan.to_f32() + src.to_f32() * gain);
+            }
+        }
+    }
+
+    /// Apply a tilt equalizer, which boosts high frequencies while cutting low
+    /// frequencies (or vice-versa for a negative slope) around `pivot_hz`.
+    ///
+    /// The filter is a first-order pair of complementary shelves, meeting at
+    /// unity gain at the pivot, where the response slopes by
+    /// `slope_db_per_oct` (limited to just under ±6 dB per octave).
+    pub fn tilt(&mut self, pivot_hz: f32, slope_db_per_oct: f32) {
+        // Maximum slope of a first-order filter (in dB per octave).
+        const MAX_SLOPE: f32 = 6.0206;
+
+        let hz = self.sample_rate().get() as f32;
+        let r = (slope_db_per_oct / MAX_SLOPE).clamp(-0.99, 0.99);
+        // Shelf gain `k` at high frequencies, `1 / k` at low frequencies.
+        let k = ((1.0 + r) / (1.0 - r)).sqrt();
+        // Bilinear transform, prewarped so that the pivot is exact.
+        let c = (core::f32::consts::PI * pivot_hz / hz).tan().recip();
+        let a0 = c + k;
+        let (b0, b1, a1) =
+            ((k * c + 1.0) / a0, (1.0 - k * c) / a0, (k - c) / a0);
+
+        let mut x1 = [0.0f32; CH];
+        let mut y1 = [0.0f32; CH];
+        for frame in self.iter_mut() {
+            for (i, chan) in frame.channels_mut().iter_mut().enumerate() {
+                let x = chan.to_f32();
+                let y = b0 * x + b1 * x1[i] - a1 * y1[i];
+                x1[i] = x;
+                y1[i] = y;
+                *chan = Chan::from(y);
+            }
+        }
+    }
+
+    /// Generate a gain envelope for ducking this audio under `key` (the
+    /// classic sidechain compressor), to be applied with
+    /// [`map()`](Self::map).
+    ///
+    /// The envelope follows the peak level of `key`, rising over `attack_ms`
+    /// and falling over `release_ms` milliseconds.  Where it goes above
+    /// `threshold_db`, the gain is reduced so the overshoot is divided by
+    /// `ratio`; elsewhere the gain is 1.  The envelope is as long as this
+    /// audio, with `key` treated as silent past its end.
+    ///
+    /// # Panics
+    /// If the sample rates of `self` and `key` don't match, or `ratio` is less
+    /// than 1.
+    pub fn sidechain_gain(
+        &self,
+        key: &Self,
+        threshold_db: f32,
+        ratio: f32,
+        attack_ms: f32,
+        release_ms: f32,
+    ) -> Audio<Ch32, 1> {
+        self.sidechain_gain_knee(
+            key,
+            threshold_db,
+            ratio,
+            0.0,
+            attack_ms,
+            release_ms,
+        )
+    }
+
+    /// Generate a gain envelope for ducking this audio under `key`, like
+    /// [`sidechain_gain()`](Self::sidechain_gain), but with a soft knee
+    /// `knee_db` wide centered on the threshold.
+    ///
+    /// Across the knee, the ratio eases in from 1 to `ratio` instead of
+    /// switching at the threshold, for more transparent compression.  A knee
+    /// of 0 dB is the same as `sidechain_gain()`.
+    ///
+    /// # Panics
+    /// If the sample rates of `self` and `key` don't match, `ratio` is less
+    /// than 1, or `knee_db` is negative.
+    pub fn sidechain_gain_knee(
+        &self,
+        key: &Self,
+        threshold_db: f32,
+        ratio: f32,
+        knee_db: f32,
+        attack_ms: f32,
+        release_ms: f32,
+    ) -> Audio<Ch32, 1> {
+        assert_eq!(self.sample_rate(), key.sample_rate());
+        assert!(ratio >= 1.0);
+        assert!(knee_db >= 0.0);
+        let hz = self.sample_rate().get();
+        let (attack, release) =
+            (smoothing(hz, attack_ms), smoothing(hz, release_ms));
+        let slope = 1.0 - ratio.recip();
+
+        let mut envelope = 0.0f32;
+        let gains = (0..self.len())
+            .map(|i| {
+                let level = key.get(i).map_or(0.0, |frame| {
+                    frame
+                        .channels()
+                        .iter()
+                        .map(|chan| chan.to_f32().abs())
+                        .fold(0.0, f32::max)
+                });
+                let coefficient =
+                    if level > envelope { attack } else { release };
+                envelope = coefficient * envelope + (1.0 - coefficient) * level;
+
+                let over_db = 20.0 * envelope.log10() - threshold_db;
+                let reduction_db = gain_reduction_db(over_db, slope, knee_db);
+                let gain =
+                    (-reduction_db / 20.0 * core::f32::consts::LN_10).exp();
+                Frame::<Ch32, 1>::new(Ch32::new(gain))
+            })
+            .collect::<Vec<_>>();
+        Audio::with_frames(self.sample_rate().get(), gains)
+    }
+
+    /// Expand the dynamic range of the audio with `expander`, continuing
+    /// from the envelope of the previous buffer.
+    pub fn expand(&mut self, expander: &mut Expander) {
+        let hz = self.sample_rate().get();
+        let (attack, release) = (
+            smoothing(hz, expander.attack_ms),
+            smoothing(hz, expander.release_ms),
+        );
+        for frame in self.iter_mut() {
+            let level = frame
+                .channels()
+                .iter()
+                .map(|chan| chan.to_f32().abs())
+                .fold(0.0, f32::max);
+            frame.gain(expander.gain(level, attack, release));
+        }
+    }
+
+    /// Apply tremolo (amplitude modulation by a sine wave [`Lfo`]).
+    ///
+    /// The gain swings between 1 and `1 - depth`, `rate_hz` times per second,
+    /// starting halfway between.
+    ///
+    /// # Panics
+    /// If `depth` is not in the range `0.0..=1.0`.
+    pub fn tremolo(&mut self, rate_hz: f32, depth: f32) {
+        assert!((0.0..=1.0).contains(&depth));
+        let hz = self.sample_rate();
+        let mut lfo = Lfo::sine(rate_hz, depth);
+        for frame in self.iter_mut() {
+            let gain = 1.0 - (depth - lfo.next(hz)) * 0.5;
+            for chan in frame.channels_mut() {
+                *chan = Chan::from(chan.to_f32() * gain);
+            }
+        }
+    }
+
+    /// Delay each channel by a (possibly fractional) number of frames, for
+    /// sub-sample alignment such as between the microphones of an array.
+    ///
+    /// The fractional part is interpolated with the same Kaiser-windowed sinc
+    /// the resampler uses.  The buffer is extended to hold the longest delay.
+    ///
+    /// # Panics
+    /// If any delay is negative or not finite.
+    pub fn fractional_delay(&mut self, per_channel: [f32; CH]) {
+        assert!(per_channel.iter().all(|d| d.is_finite() && *d >= 0.0));
+        let half = FRACTIONAL_DELAY_TAPS / 2;
+        let longest = per_channel.iter().fold(0.0f32, |a, &d| a.max(d));
+        let len = self.len();
+        self.frames
+            .resize(len + longest.ceil() as usize, Frame::default());
+
+        for (chan, delay) in per_channel.iter().enumerate() {
+            let whole = delay.floor() as isize;
+            let frac = delay - delay.floor();
+            let taps = (1 - half..=half)
+                .map(|t| {
+                    let x = t as f32 - frac;
+                    sinc(1.0, x, FRACTIONAL_DELAY_TAPS, Window::default())
+                })
+                .collect::<Vec<_>>();
+            let input = self.frames[..len]
+                .iter()
+                .map(|frame| frame.channels()[chan].to_f32())
+                .collect::<Vec<_>>();
+            for (i, frame) in self.frames.iter_mut().enumerate() {
+                let mut sum = 0.0;
+                for (t, tap) in (1 - half..=half).zip(taps.iter()) {
+                    let j = i as isize - whole - t as isize;
+                    if j >= 0 && (j as usize) < len {
+                        sum += tap * input[j as usize];
+                    }
+                }
+                frame.channels_mut()[chan] = Chan::from(sum);
+            }
+        }
+    }
+}
+
+/// A side of a stereo image.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Side {
+    /// Left channel.
+    Left,
+    /// Right channel.
+    Right,
+}
+
+impl<Chan: Channel> Audio<Chan, 2> {
+    /// Widen the stereo image using the Haas (precedence) effect, by delaying
+    /// the channel on `side` by `delay_ms` milliseconds.  The buffer is
+    /// extended to hold the delayed channel.
+    ///
+    /// The sound is perceived as coming from the other (earlier) side, and
+    /// wider.  Beware that summing the result to mono causes comb filtering,
+    /// so this isn't mono-compatible.
+    ///
+    /// # Panics
+    /// If `delay_ms` is not in the range `0.0..=30.0` (longer delays are heard
+    /// as a separate echo).
+    pub fn haas_widen(&mut self, delay_ms: f32, side: Side) {
+        assert!((0.0..=30.0).contains(&delay_ms));
+        let hz = self.sample_rate().get() as f32;
+        let delay = (delay_ms * hz / 1000.0).round() as usize;
+        let chan = match side {
+            Side::Left => 0,
+            Side::Right => 1,
+        };
+
+        let len = self.len();
+        self.frames.resize(len + delay, Frame::default());
+        for i in (0..len + delay).rev() {
+            self.frames[i].channels_mut()[chan] = if i >= delay {
+                self.frames[i - delay].channels()[chan]
+            } else {
+                Chan::default()
+            };
+        }
+    }
+}
+
+impl<Chan: Channel> Audio<Chan, 1> {
+    /// Place a mono source in 3D for listening over headphones, using a simple
+    /// spherical head approximation of a head-related transfer function.
+    ///
+    /// `azimuth` is in degrees clockwise from straight ahead (90 is right, -90
+    /// is left), and `elevation` is in degrees above the horizon.  The far ear
+    /// is delayed (interaural time difference), attenuated and low-pass
+    /// filtered (interaural level difference / head shadow).  The output is
+    /// extended to hold the delayed far ear.
+    pub fn to_binaural(&self, azimuth: f32, elevation: f32) -> Audio<Chan, 2> {
+        const LEFT: usize = 0;
+        const RIGHT: usize = 1;
+
+        let hz = self.sample_rate().get() as f32;
+        let (azimuth, elevation) =
+            (azimuth.to_radians(), elevation.to_radians());
+        // Angle away from the median plane, positive is right.
+        let lateral = (azimuth.sin() * elevation.cos()).clamp(-1.0, 1.0).asin();
+        let theta = lateral.abs();
+        let side = theta.sin();
+
+        // Woodworth's formula for the interaural time difference.
+        let itd = HEAD_RADIUS / SPEED_OF_SOUND * (theta + side);
+        let delay = (itd * hz).round() as usize;
+        let gain = (-MAX_ILD_DB * side / 20.0 * core::f32::consts::LN_10).exp();
+        // One-pole low-pass, blended in as the source moves to the side.
+        let a = (-2.0 * core::f32::consts::PI * HEAD_SHADOW_HZ / hz).exp();
+        let (near, far) = if lateral < 0.0 {
+            (LEFT, RIGHT)
+        } else {
+            (RIGHT, LEFT)
+        };
+
+        let mut frames = vec![Frame::<Chan, 2>::default(); self.len() + delay];
+        let mut shadow = 0.0;
+        let sample =
+            |i| self.get(i).map(|f| f.channels()[0].to_f32()).unwrap_or(0.0);
+        for (i, frame) in frames.iter_mut().enumerate() {
+            frame.channels_mut()[near] = Chan::from(sample(i));
+            let x = if i >= delay { sample(i - delay) } else { 0.0 };
+            shadow = (1.0 - a) * x + a * shadow;
+            frame.channels_mut()[far] =
+                Chan::from(gain * ((1.0 - side) * x + side * shadow));
+        }
+        Audio::with_frames(self.sample_rate().get(), frames)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{gain_reduction_db, Expander, Side};
+    use crate::chan::{Ch16, Ch32, Channel};
+    use crate::{Audio, Frame};
+    use alloc::{vec, vec::Vec};
+
+    #[test]
+    fn normalize_per_channel() {
+        let mut audio = Audio::<Ch32, 2>::with_frames(
+            48_000,
+            vec![
+                Frame::<Ch32, 2>::new(Ch32::new(0.5), Ch32::new(-0.125)),
+                Frame::<Ch32, 2>::new(Ch32::new(-0.25), Ch32::new(0.25)),
+                Frame::<Ch32, 2>::new(Ch32::new(0.0), Ch32::new(0.0)),
+            ],
+        );
+        audio.normalize_per_channel();
+        for chan in 0..2 {
+            let peak = audio
+                .iter()
+                .map(|f| f.channels()[chan].to_f32().abs())
+                .fold(0.0, f32::max);
+            assert!((peak - Ch32::MAX.to_f32()).abs() < 1e-6);
+        }
+        assert_eq!(audio.get(0).unwrap().channels()[0], Ch32::new(1.0));
+        assert_eq!(audio.get(0).unwrap().channels()[1], Ch32::new(-0.5));
+    }
+
+    #[test]
+    fn apply_calibration() {
+        let mut audio = Audio::<Ch32, 2>::with_f32_buffer(
+            48_000,
+            vec![0.5, 0.5, -0.25, -0.25],
+        );
+        audio.apply_calibration([1.0, 0.5], [false, true]);
+        assert_eq!(audio.as_f32_slice(), &[0.5, -0.25, -0.25, 0.125][..]);
+    }
+
+    #[test]
+    fn convolve_fir() {
+        let samples = [0.5, -0.25, 1.0, 0.0, 0.75, -1.0];
+        let audio = Audio::<Ch32, 2>::with_f32_buffer(48_000, &samples[..]);
+
+        let mut identity =
+            Audio::<Ch32, 2>::with_f32_buffer(48_000, &samples[..]);
+        identity.convolve_fir(&[1.0]);
+        assert_eq!(identity.as_slice(), audio.as_slice());
+
+        let mut average = audio;
+        average.convolve_fir(&[0.5, 0.5]);
+        let expected = Audio::<Ch32, 2>::with_f32_buffer(
+            48_000,
+            vec![0.25, -0.125, 0.75, -0.125, 0.875, -0.5],
+        );
+        assert_eq!(average.as_slice(), expected.as_slice());
+    }
+
+    #[test]
+    fn echo() {
+        let mut impulse = vec![Frame::<Ch32, 1>::default(); 5];
+        impulse[0] = Frame::<Ch32, 1>::new(Ch32::new(1.0));
+
+        // 10 ms at 1 kHz is 10 frames.
+        let mut audio = Audio::with_frames(1_000, impulse.clone());
+        audio.echo(10.0, 0.5, 1.0);
+        let mut level = 1.0;
+        for (i, frame) in audio.iter().enumerate().skip(1) {
+            let sample = frame.channels()[0].to_f32();
+            if i % 10 == 0 {
+                assert_eq!(sample, level);
+                level *= 0.5;
+            } else {
+                assert_eq!(sample, 0.0);
+            }
+        }
+        assert!(level < 0.001);
+
+        // No feedback gives exactly one echo.
+        let mut audio = Audio::with_frames(1_000, impulse);
+        audio.echo(10.0, 0.0, 0.5);
+        assert_eq!(audio.len(), 15);
+        for (i, frame) in audio.iter().enumerate() {
+            let expected = match i {
+                0 => 1.0,
+                10 => 0.5,
+                _ => 0.0,
+            };
+            assert_eq!(frame.channels()[0].to_f32(), expected);
+        }
+    }
+
+    #[test]
+    fn to_binaural() {
+        let mut frames = vec![Frame::<Ch32, 1>::default(); 100];
+        for (i, frame) in frames.iter_mut().enumerate().take(20) {
+            *frame = Frame::<Ch32, 1>::new(Ch32::new(1.0 - i as f32 / 20.0));
+        }
+        let audio = Audio::with_frames(48_000, frames);
+
+        let onset = |audio: &Audio<Ch32, 2>, chan: usize| {
+            audio
+                .iter()
+                .position(|f| f.channels()[chan].to_f32() != 0.0)
+                .unwrap()
+        };
+        let peak = |audio: &Audio<Ch32, 2>, chan: usize| {
+            audio
+                .iter()
+                .map(|f| f.channels()[chan].to_f32().abs())
+                .fold(0.0, f32::max)
+        };
+
+        let right = audio.to_binaural(90.0, 0.0);
+        assert!(onset(&right, 1) < onset(&right, 0));
+        assert!(peak(&right, 1) > peak(&right, 0));
+        // About 0.66 ms
+        assert_eq!(onset(&right, 0) - onset(&right, 1), 31);
+
+        let left = audio.to_binaural(-90.0, 0.0);
+        assert!(onset(&left, 0) < onset(&left, 1));
+        assert!(peak(&left, 0) > peak(&left, 1));
+
+        // Straight ahead (or overhead) is the same in both ears.
+        for (azimuth, elevation) in [(0.0, 0.0), (90.0, 90.0)] {
+            let center = audio.to_binaural(azimuth, elevation);
+            for frame in center.iter() {
+                let [l, r] = *frame.channels();
+                assert!((l.to_f32() - r.to_f32()).abs() < 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn mix_auto() {
+        let full = Audio::<Ch16, 2>::with_i16_buffer(
+            48_000,
+            vec![i16::MAX, i16::MIN, -16_384, 16_384],
+        );
+        let sources = [&full; 4];
+        let (mix, gain) = Audio::mix_auto(48_000, &sources);
+        assert_eq!(mix.len(), 2);
+        assert!((gain - 0.25).abs() < 1e-4);
+        assert_eq!(mix.get(0).unwrap().channels()[0], Ch16::MAX);
+        assert_eq!(mix.get(0).unwrap().channels()[1], Ch16::MIN);
+        let half = mix.get(1).unwrap().channels()[1].to_f32();
+        assert!((half - 0.5).abs() < 1e-3);
+
+        // Quiet enough mixes are left alone.
+        let quiet = Audio::<Ch16, 2>::with_i16_buffer(48_000, vec![1000; 8]);
+        let (mix, gain) = Audio::mix_auto(48_000, &[&quiet, &quiet]);
+        assert_eq!(gain, 1.0);
+        assert_eq!(mix.len(), 4);
+        let sum = mix.get(3).unwrap().channels()[0].to_f32();
+        assert!((sum - 2.0 * Ch16::new(1000).to_f32()).abs() < 1e-4);
+    }
+
+    #[test]
+    fn gain_ramp_db() {
+        let mut audio = Audio::<Ch32, 1>::with_f32_buffer(1_000, vec![1.0; 5]);
+        audio.gain_ramp_db(0.0, -40.0);
+        let gains = audio
+            .iter()
+            .map(|frame| frame.channels()[0].to_f32())
+            .collect::<Vec<_>>();
+        for (gain, expected) in gains.iter().zip([1.0, 0.316_23, 0.1, 0.031_62])
+        {
+            assert!((gain - expected).abs() < 1e-5, "{:?}", gains);
+        }
+        // The midpoint is at -20 dB, not halfway between 1 and 0.01.
+        assert!((gains[2] - 0.505).abs() > 0.4);
+        assert!((gains[4] - 0.01).abs() < 1e-6);
+    }
+
+    #[test]
+    fn mix_automated() {
+        let mut mix = Audio::<Ch32, 2>::with_f32_buffer(1_000, vec![0.25; 8]);
+        let source = Audio::<Ch32, 2>::with_f32_buffer(1_000, vec![0.5; 12]);
+        // Fade in over 4 frames.
+        mix.mix_automated(&source, |i| (i as f32 / 4.0).min(1.0));
+        assert_eq!(mix.len(), 6);
+        let left = mix
+            .iter()
+            .map(|frame| frame.channels()[0].to_f32())
+            .collect::<Vec<_>>();
+        assert_eq!(left, [0.25, 0.375, 0.5, 0.625, 0.5, 0.5]);
+    }
+
+    #[test]
+    fn haas_widen() {
+        let frames = (0..100)
+            .map(|i| {
+                let x = Ch32::new(i as f32 / 100.0);
+                Frame::<Ch32, 2>::new(x, -x)
+            })
+            .collect::<Vec<_>>();
+        let dry = Audio::with_frames(1_000, frames);
+
+        // 10 ms at 1 kHz is 10 frames.
+        let mut audio = Audio::with_frames(1_000, dry.as_slice());
+        audio.haas_widen(10.0, Side::Right);
+        assert_eq!(audio.len(), 110);
+        for (i, frame) in audio.iter().enumerate() {
+            let [left, right] = *frame.channels();
+            let dry = |i| dry.get(i).map(|f| *f.channels()).unwrap_or_default();
+            assert_eq!(left, dry(i)[0]);
+            let delayed = if i >= 10 {
+                dry(i - 10)[1]
+            } else {
+                Ch32::default()
+            };
+            assert_eq!(right, delayed);
+        }
+
+        let mut audio = Audio::with_frames(1_000, dry.as_slice());
+        audio.haas_widen(5.0, Side::Left);
+        assert_eq!(audio.get(5).unwrap().channels()[0], Ch32::new(0.0));
+        assert_eq!(audio.get(6).unwrap().channels()[0], Ch32::new(0.01));
+        assert_eq!(audio.get(6).unwrap().channels()[1], Ch32::new(-0.06));
+    }
+
+    #[test]
+    fn fractional_delay() {
+        // 1 kHz at 48 kHz, so half a frame is 3.75 degrees of phase.
+        let step = 2.0 * core::f32::consts::PI * 1000.0 / 48_000.0;
+        let frames = (0..4800)
+            .map(|i| {
+                let x = Ch32::new((i as f32 * step).sin());
+                Frame::<Ch32, 2>::new(x, x)
+            })
+            .collect::<Vec<_>>();
+        let mut audio = Audio::with_frames(48_000, frames);
+        audio.fractional_delay([0.5, 0.0]);
+        assert_eq!(audio.len(), 4801);
+        for i in 100..4700 {
+            let [left, right] = *audio.get(i).unwrap().channels();
+            let expected = ((i as f32 - 0.5) * step).sin();
+            assert!((left.to_f32() - expected).abs() < 0.001);
+            assert!((right.to_f32() - (i as f32 * step).sin()).abs() < 1e-6);
+        }
+    }
+
+    fn sine(hz: u32, freq: f32, len: usize) -> Audio<Ch32, 1> {
+        let step = 2.0 * core::f32::consts::PI * freq / hz as f32;
+        let frames = (0..len)
+            .map(|i| Frame::<Ch32, 1>::new(Ch32::new((i as f32 * step).sin())))
+            .collect::<Vec<_>>();
+        Audio::with_frames(hz, frames)
+    }
+
+    fn energy(audio: &Audio<Ch32, 1>) -> f32 {
+        audio
+            .iter()
+            .skip(audio.len() / 2)
+            .map(|f| f.channels()[0].to_f32().powi(2))
+            .sum()
+    }
+
+    #[test]
+    fn tilt() {
+        let mut low = sine(48_000, 100.0, 9600);
+        let mut high = sine(48_000, 8000.0, 9600);
+        let (low_in, high_in) = (energy(&low), energy(&high));
+        low.tilt(1000.0, 3.0);
+        high.tilt(1000.0, 3.0);
+        assert!(energy(&low) < low_in * 0.5);
+        assert!(energy(&high) > high_in * 2.0);
+
+        let mut high = sine(48_000, 8000.0, 9600);
+        high.tilt(1000.0, -3.0);
+        assert!(energy(&high) < high_in * 0.5);
+    }
+
+    #[test]
+    fn sidechain_gain() {
+        // Loud for the first half second, then quiet.
+        let key = (0..48_000)
+            .map(|i| {
+                let level = if i < 24_000 { 0.8 } else { 0.01 };
+                let x = (i as f32 * 0.05).sin() * level;
+                Frame::<Ch32, 2>::new(Ch32::new(x), Ch32::new(x))
+            })
+            .collect::<Vec<_>>();
+        let key = Audio::with_frames(48_000, key);
+        let music = Audio::<Ch32, 2>::with_silence(48_000, 48_000);
+        let gain = music.sidechain_gain(&key, -20.0, 4.0, 5.0, 50.0);
+        assert_eq!(gain.len(), 48_000);
+        let at = |i: usize| gain.get(i).unwrap().channels()[0].to_f32();
+
+        // 0.8 is about 18 dB over, reduced by 13.5 dB at 4:1.
+        for i in (4_800..24_000).step_by(100) {
+            assert!(at(i) < 0.25, "{}", at(i));
+        }
+        // Back to unity once the release is over.
+        for i in (36_000..48_000).step_by(100) {
+            assert_eq!(at(i), 1.0);
+        }
+        assert_eq!(at(0), 1.0);
+    }
+
+    #[test]
+    fn gain_reduction_knee() {
+        // 4:1 with a 6 dB knee.
+        let reduction = |over_db| gain_reduction_db(over_db, 0.75, 6.0);
+        assert_eq!(reduction(-3.0), 0.0);
+        assert_eq!(reduction(3.0), 2.25);
+        assert_eq!(reduction(10.0), 7.5);
+        assert_eq!(gain_reduction_db(2.0, 0.75, 0.0), 1.5);
+        assert_eq!(gain_reduction_db(-2.0, 0.75, 0.0), 0.0);
+
+        // In the knee, between no reduction and the full ratio applied from
+        // the bottom of the knee, and rising smoothly.
+        let mut last = 0.0;
+        for i in -29..30 {
+            let over_db = i as f32 / 10.0;
+            let db = reduction(over_db);
+            assert!(db > last);
+            assert!(db < 0.75 * (over_db + 3.0));
+            last = db;
+        }
+
+        // A steady key 1 dB under the threshold is only reduced by the knee.
+        let level = (-21.0 / 20.0 * core::f32::consts::LN_10).exp();
+        let key = Audio::<Ch32, 1>::with_f32_buffer(48_000, vec![level; 4800]);
+        let hard = key.sidechain_gain(&key, -20.0, 4.0, 1.0, 50.0);
+        let soft = key.sidechain_gain_knee(&key, -20.0, 4.0, 6.0, 1.0, 50.0);
+        let at = |gain: &Audio<Ch32, 1>| gain.get(4799).unwrap().channels()[0];
+        assert_eq!(at(&hard).to_f32(), 1.0);
+        let expected = (-0.25 / 20.0 * core::f32::consts::LN_10).exp();
+        assert!((at(&soft).to_f32() - expected).abs() < 1e-4);
+    }
+
+    #[test]
+    fn tremolo() {
+        // 10 Hz at 48 kHz is a period of 4800 frames, peaking a quarter in.
+        let mut audio =
+            Audio::<Ch32, 2>::with_f32_buffer(48_000, vec![0.8; 57600]);
+        audio.tremolo(10.0, 0.5);
+        let level = |i: usize| audio.get(i).unwrap().channels()[1].to_f32();
+        for period in 0..6 {
+            let start = period * 4800;
+            let peak = (start..start + 4800).fold(start, |p, i| {
+                if level(i) > level(p) {
+                    i
+                } else {
+                    p
+                }
+            });
+            assert!((peak as isize - (start + 1200) as isize).abs() <= 4);
+        }
+        assert!((level(1200) - 0.8).abs() < 0.0001);
+        assert!((level(3600) - 0.4).abs() < 0.0001);
+    }
+
+    #[test]
+    fn gate() {
+        let mut audio = Audio::<Ch32, 2>::with_f32_buffer(
+            48_000,
+            [0.001, -0.002, 0.5, -0.01, -0.75, 0.25],
+        );
+        audio.gate(0.01);
+        let expected = Audio::<Ch32, 2>::with_f32_buffer(
+            48_000,
+            [0.0, 0.0, 0.5, -0.01, -0.75, 0.25],
+        );
+        assert_eq!(audio.as_slice(), expected.as_slice());
+    }
+
+    #[test]
+    fn declip() {
+        let clean = sine(48_000, 480.0, 1000);
+        let mut clipped = Audio::with_frames(48_000, clean.as_slice());
+        for frame in clipped.iter_mut() {
+            let x = frame.channels()[0].to_f32();
+            *frame = Frame::<Ch32, 1>::new(Ch32::new(x.clamp(-0.8, 0.8)));
+        }
+        let error = |audio: &Audio<Ch32, 1>| {
+            audio
+                .iter()
+                .zip(clean.iter())
+                .map(|(a, b)| {
+                    (a.channels()[0].to_f32() - b.channels()[0].to_f32())
+                        .powi(2)
+                })
+                .sum::<f32>()
+        };
+        let before = error(&clipped);
+        clipped.declip(0.8);
+        let after = error(&clipped);
+        assert!(after < before * 0.1, "{} -> {}", before, after);
+        assert!(clipped.iter().any(|f| f.channels()[0].to_f32() > 0.9));
+
+        // Audio under the threshold is left alone.
+        let mut quiet = Audio::with_frames(48_000, clean.as_slice());
+        quiet.map(|frame| frame * Ch32::new(0.5));
+        let mut audio = Audio::with_frames(48_000, quiet.as_slice());
+        audio.declip(0.8);
+        assert_eq!(audio.as_slice(), quiet.as_slice());
+    }
+
+    #[test]
+    fn gain_masked() {
+        let mut audio = Audio::<Ch32, 2>::with_f32_buffer(
+            48_000,
+            [0.5, -0.5, 0.25, 1.0, -0.75, 0.125],
+        );
+        audio.gain_masked(&[true, false, true], 0.5);
+        let expected = Audio::<Ch32, 2>::with_f32_buffer(
+            48_000,
+            [0.25, -0.25, 0.25, 1.0, -0.375, 0.0625],
+        );
+        assert_eq!(audio.as_slice(), expected.as_slice());
+    }
+
+    /// Expand a steady tone at `db`, and get its level in decibels after.
+    fn expanded(mut expander: Expander, db: f32) -> f32 {
+        let level = (db / 20.0 * core::f32::consts::LN_10).exp();
+        let samples = (0..9600)
+            .map(|i| if i % 2 == 0 { level } else { -level })
+            .collect::<Vec<_>>();
+        let mut audio = Audio::<Ch32, 2>::with_f32_buffer(48_000, samples);
+        audio.expand(&mut expander);
+        let last = audio.get(4799).unwrap().channels()[0];
+        20.0 * last.to_f32().abs().log10()
+    }
+
+    #[test]
+    fn expander() {
+        // 2:1 under -30 dB: 10 dB under becomes 20 dB under.
+        let expander = Expander::new(-30.0, 2.0, 1.0, 10.0);
+        assert!((expanded(expander, -40.0) + 50.0).abs() < 0.1);
+        assert!((expanded(expander, -10.0) + 10.0).abs() < 0.01);
+
+        // A gate silences everything under the threshold.
+        let gate = Expander::gate(-30.0, 1.0, 10.0);
+        assert_eq!(expanded(gate, -40.0), f32::NEG_INFINITY);
+        assert!((expanded(gate, -10.0) + 10.0).abs() < 0.01);
+
+        // 1:1 does nothing, even to silence.
+        let unity = Expander::new(-30.0, 1.0, 1.0, 10.0);
+        assert!((expanded(unity, -40.0) + 40.0).abs() < 0.01);
+        let mut silence = Audio::<Ch32, 1>::with_silence(48_000, 10);
+        silence.expand(&mut Expander::new(-30.0, 1.0, 1.0, 10.0));
+        assert!(silence.iter().all(|f| f.channels()[0].to_f32() == 0.0));
+
+        // 1.5:1 upward over -20 dB: 10 dB over becomes 15 dB over.
+        let upward = Expander::upward(-20.0, 1.5, 1.0, 10.0);
+        assert!((expanded(upward, -10.0) + 5.0).abs() < 0.1);
+        assert!((expanded(upward, -40.0) + 40.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn expander_continues() {
+        let mut audio =
+            Audio::<Ch32, 1>::with_f32_buffer(48_000, vec![0.001; 4800]);
+        let mut expander = Expander::new(-30.0, 4.0, 1.0, 10.0);
+        let mut whole = Audio::with_frames(48_000, audio.as_slice());
+        whole.expand(&mut expander);
+
+        let mut expander = Expander::new(-30.0, 4.0, 1.0, 10.0);
+        let mut second = Audio::with_frames(48_000, &audio.as_slice()[2400..]);
+        audio.truncate(2400);
+        audio.expand(&mut expander);
+        second.expand(&mut expander);
+        assert_eq!(audio.as_slice(), &whole.as_slice()[..2400]);
+        assert_eq!(second.as_slice(), &whole.as_slice()[2400..]);
+    }
+}
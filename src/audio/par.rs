@@ -0,0 +1,56 @@
+// Copyright © 2020-2022 The Fon Contributors.
+//
+// Licensed under any of:
+// - Apache License, Version 2.0 (https://www.apache.org/licenses/LICENSE-2.0)
+// - MIT License (https://mit-license.org/)
+// - Boost Software License, Version 1.0 (https://www.boost.org/LICENSE_1_0.txt)
+// At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
+// LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
+
+use crate::chan::Channel;
+use crate::frame::Frame;
+use crate::Audio;
+
+use rayon::prelude::*;
+
+impl<Chan: Channel + Send, const CH: usize> Audio<Chan, CH> {
+    /// Transform each audio frame in parallel, across threads (requires the
+    /// `rayon` feature).
+    ///
+    /// Produces the same result as [`map()`](Self::map), but faster for
+    /// expensive transforms on large buffers.
+    pub fn par_map<F>(&mut self, f: F)
+    where
+        F: Fn(Frame<Chan, CH>) -> Frame<Chan, CH> + Sync,
+    {
+        self.frames
+            .par_iter_mut()
+            .for_each(|frame| *frame = f(*frame));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chan::{Ch16, Ch32};
+    use crate::{Audio, Frame};
+    use alloc::vec::Vec;
+
+    #[test]
+    fn par_map() {
+        let frames = (0..100_000)
+            .map(|i| {
+                let x = i as f32 * 0.001;
+                Frame::<Ch32, 2>::new(Ch32::new(x.sin()), Ch32::new(x.cos()))
+            })
+            .collect::<Vec<_>>();
+        let f = |frame: Frame<Ch32, 2>| {
+            (frame * Ch32::new(1.5)).to::<Ch16, 2>().to::<Ch32, 2>()
+        };
+
+        let mut serial = Audio::with_frames(48_000, frames.as_slice());
+        serial.map(f);
+        let mut parallel = Audio::with_frames(48_000, frames.as_slice());
+        parallel.par_map(f);
+        assert_eq!(serial.as_slice(), parallel.as_slice());
+    }
+}
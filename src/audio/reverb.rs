@@ -0,0 +1,199 @@
+// Copyright © 2020-2022 The Fon Contributors.
+//
+// Licensed under any of:
+// - Apache License, Version 2.0 (https://www.apache.org/licenses/LICENSE-2.0)
+// - MIT License (https://mit-license.org/)
+// - Boost Software License, Version 1.0 (https://www.boost.org/LICENSE_1_0.txt)
+// At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
+// LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
+
+use_libm!();
+
+use crate::chan::Channel;
+use crate::frame::Frame;
+use crate::Audio;
+
+use alloc::{vec, vec::Vec};
+
+/// Sample rate the delay lengths are tuned for (in hertz).
+const TUNING_HZ: f32 = 44_100.0;
+/// Delay lengths of the parallel comb filters (in frames at `TUNING_HZ`).
+const COMB_TUNING: [usize; 8] =
+    [1116, 1188, 1277, 1356, 1422, 1491, 1557, 1617];
+/// Delay lengths of the series allpass filters (in frames at `TUNING_HZ`).
+const ALLPASS_TUNING: [usize; 4] = [556, 441, 341, 225];
+/// Extra delay added for each channel, so channels decorrelate.
+const CHANNEL_SPREAD: usize = 23;
+/// Gain of the input into the comb filters.
+const INPUT_GAIN: f32 = 0.015;
+/// Gain of the reverb output.
+const OUTPUT_GAIN: f32 = 3.0;
+/// Level below which the reverb tail is dropped.
+const TAIL_FLOOR: f32 = 0.001;
+
+/// Lowpass-feedback comb filter.
+struct Comb {
+    buffer: Vec<f32>,
+    index: usize,
+    store: f32,
+}
+
+impl Comb {
+    fn new(len: usize) -> Self {
+        Self {
+            buffer: vec![0.0; len],
+            index: 0,
+            store: 0.0,
+        }
+    }
+
+    fn process(&mut self, input: f32, feedback: f32, damping: f32) -> f32 {
+        let output = self.buffer[self.index];
+        self.store = output * (1.0 - damping) + self.store * damping;
+        if self.store.is_subnormal() {
+            self.store = 0.0;
+        }
+        self.buffer[self.index] = input + self.store * feedback;
+        self.index = (self.index + 1) % self.buffer.len();
+        output
+    }
+}
+
+/// Schroeder allpass filter.
+struct Allpass {
+    buffer: Vec<f32>,
+    index: usize,
+}
+
+impl Allpass {
+    fn new(len: usize) -> Self {
+        Self {
+            buffer: vec![0.0; len],
+            index: 0,
+        }
+    }
+
+    fn process(&mut self, input: f32) -> f32 {
+        let delayed = self.buffer[self.index];
+        self.buffer[self.index] = input + delayed * 0.5;
+        self.index = (self.index + 1) % self.buffer.len();
+        delayed - input
+    }
+}
+
+impl<Chan: Channel, const CH: usize> Audio<Chan, CH> {
+    /// Add reverb to the audio, using the Freeverb topology (eight parallel
+    /// comb filters into four series allpass filters for each channel).
+    ///
+    /// A larger `room_size` (from 0 to 1) gives a longer decay, and more
+    /// `damping` (from 0 to 1) absorbs high frequencies faster.  The reverb
+    /// is mixed in at a level of `wet`.  The buffer is extended to hold the
+    /// reverb tail, which ends once it decays below -60 dB.
+    ///
+    /// # Panics
+    /// If `room_size` or `damping` is not in the range `0.0..=1.0`.
+    pub fn reverb(&mut self, room_size: f32, damping: f32, wet: f32) {
+        assert!((0.0..=1.0).contains(&room_size));
+        assert!((0.0..=1.0).contains(&damping));
+        if wet == 0.0 {
+            return;
+        }
+
+        let scale = self.sample_rate().get() as f32 / TUNING_HZ;
+        let delay = |len: usize, chan: usize| {
+            (((len + chan * CHANNEL_SPREAD) as f32 * scale).round() as usize)
+                .max(1)
+        };
+        let feedback = room_size * 0.28 + 0.7;
+        let damping = damping * 0.4;
+
+        // Number of trips around the longest comb filter before the tail
+        // decays below the floor.
+        let trips = (TAIL_FLOOR.ln() / feedback.ln()).ceil() as usize;
+        let last = CH.saturating_sub(1);
+        let longest = delay(COMB_TUNING[7], last);
+        let allpass: usize =
+            ALLPASS_TUNING.iter().map(|&len| delay(len, last)).sum();
+        let len = self.len() + trips * longest + allpass;
+        self.frames.resize(len, Frame::default());
+
+        for chan in 0..CH {
+            let mut combs = COMB_TUNING
+                .iter()
+                .map(|&len| Comb::new(delay(len, chan)))
+                .collect::<Vec<_>>();
+            let mut allpasses = ALLPASS_TUNING
+                .iter()
+                .map(|&len| Allpass::new(delay(len, chan)))
+                .collect::<Vec<_>>();
+            for frame in self.frames.iter_mut() {
+                let sample = &mut frame.channels_mut()[chan];
+                let dry = sample.to_f32();
+                let input = dry * INPUT_GAIN;
+                let mut output = combs
+                    .iter_mut()
+                    .map(|comb| comb.process(input, feedback, damping))
+                    .sum::<f32>();
+                for allpass in allpasses.iter_mut() {
+                    output = allpass.process(output);
+                }
+                *sample = Chan::from(dry + wet * OUTPUT_GAIN * output);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chan::{Ch32, Channel};
+    use crate::{Audio, Frame};
+    use alloc::vec::Vec;
+
+    fn impulse() -> Audio<Ch32, 2> {
+        let mut audio = Audio::<Ch32, 2>::with_silence(44_100, 4410);
+        *audio.get_mut(0).unwrap() =
+            Frame::<Ch32, 2>::new(Ch32::new(1.0), Ch32::new(1.0));
+        audio
+    }
+
+    #[test]
+    fn reverb() {
+        let mut audio = impulse();
+        audio.reverb(0.8, 0.5, 1.0);
+        assert!(audio.len() > 44_100);
+        let samples = audio
+            .iter()
+            .map(|frame| frame.channels()[0].to_f32())
+            .collect::<Vec<_>>();
+
+        // Dense: once the shortest filters have echoed, almost every frame has
+        // some reverb.
+        let nonzero = samples[2_000..22_050]
+            .iter()
+            .filter(|sample| sample.abs() > 1e-6)
+            .count();
+        assert!(nonzero > 20_050 * 9 / 10, "{}", nonzero);
+
+        // Decaying.
+        let energy = |chunk: &[f32]| chunk.iter().map(|x| x * x).sum::<f32>();
+        let energies =
+            samples[1..].chunks(22_050).map(energy).collect::<Vec<_>>();
+        for pair in energies.windows(2) {
+            assert!(pair[1] < pair[0]);
+        }
+        assert!(samples[samples.len() - 100..]
+            .iter()
+            .all(|x| x.abs() < 0.001));
+
+        // Channels are decorrelated.
+        let right = audio.get(3000).unwrap().channels()[1].to_f32();
+        assert_ne!(samples[3000], right);
+    }
+
+    #[test]
+    fn reverb_dry() {
+        let mut audio = impulse();
+        audio.reverb(0.8, 0.5, 0.0);
+        assert_eq!(audio.as_slice(), impulse().as_slice());
+    }
+}
@@ -0,0 +1,156 @@
+// Copyright © 2020-2022 The Fon Contributors.
+//
+// Licensed under any of:
+// - Apache License, Version 2.0 (https://www.apache.org/licenses/LICENSE-2.0)
+// - MIT License (https://mit-license.org/)
+// - Boost Software License, Version 1.0 (https://www.boost.org/LICENSE_1_0.txt)
+// At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
+// LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
+
+use_libm!();
+
+use crate::chan::Channel;
+use crate::frame::Frame;
+use crate::Audio;
+
+use alloc::{vec, vec::Vec};
+use core::f32::consts::PI;
+
+/// Length of each overlapping segment (in milliseconds).
+const SEGMENT_MS: f32 = 20.0;
+/// Lowest window sum that output is normalized by, so the fade in at the
+/// start doesn't blow up.
+const MIN_WINDOW_SUM: f32 = 0.1;
+
+impl<Chan: Channel, const CH: usize> Audio<Chan, CH> {
+    /// Change the duration by `factor` without changing the pitch (time
+    /// stretch), in a new buffer `factor` times as long.
+    ///
+    /// Uses WSOLA (waveform similarity overlap-add): 20 ms segments of the
+    /// input are overlapped at a fixed hop in the output, and each segment is
+    /// shifted (by up to a quarter of its length) to line up with the
+    /// waveform of the one before, to avoid phase cancellation.  This works
+    /// best on monophonic material such as speech; large factors on complex
+    /// mixes can sound phasey or stuttery.
+    ///
+    /// # Panics
+    /// If `factor` isn't positive.
+    pub fn stretch(&self, factor: f32) -> Self {
+        assert!(factor > 0.0);
+        let hz = self.sample_rate().get();
+        let len = (self.len() as f32 * factor).round() as usize;
+        let segment =
+            ((hz as f32 * SEGMENT_MS / 1000.0) as usize / 2 * 2).max(4);
+        let hop = segment / 2;
+        let tolerance = (segment / 4) as isize;
+        let window = (0..segment)
+            .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / segment as f32).cos())
+            .collect::<Vec<_>>();
+
+        // Segments are lined up using the sum of the channels.
+        let mono = self
+            .iter()
+            .map(|frame| frame.channels().iter().map(|x| x.to_f32()).sum())
+            .collect::<Vec<f32>>();
+        let sample = |i: isize| {
+            if i < 0 {
+                0.0
+            } else {
+                mono.get(i as usize).cloned().unwrap_or(0.0)
+            }
+        };
+
+        let mut out = vec![[0.0f32; CH]; len + segment];
+        let mut sums = vec![0.0f32; len + segment];
+        let mut prev = 0;
+        for k in 0..(len + hop - 1) / hop {
+            let nominal = ((k * hop) as f32 / factor).round() as isize;
+            let start = if k == 0 {
+                0
+            } else {
+                // Find the segment most like the natural continuation of the
+                // previous one.
+                let similarity = |start: isize| {
+                    (0..segment as isize)
+                        .map(|i| {
+                            sample(prev + hop as isize + i) * sample(start + i)
+                        })
+                        .sum::<f32>()
+                };
+                let mut best = (nominal, f32::NEG_INFINITY);
+                for offset in -tolerance..=tolerance {
+                    let candidate = nominal + offset;
+                    let score = similarity(candidate);
+                    if score > best.1 {
+                        best = (candidate, score);
+                    }
+                }
+                best.0
+            };
+
+            for (i, gain) in window.iter().enumerate() {
+                let src = start + i as isize;
+                if src >= 0 && (src as usize) < self.len() {
+                    let frame = self.frames[src as usize];
+                    for (out, x) in
+                        out[k * hop + i].iter_mut().zip(frame.channels())
+                    {
+                        *out += gain * x.to_f32();
+                    }
+                }
+                sums[k * hop + i] += gain;
+            }
+            prev = start;
+        }
+
+        let frames = out[..len]
+            .iter()
+            .zip(sums.iter())
+            .map(|(samples, sum)| {
+                let sum = sum.max(MIN_WINDOW_SUM);
+                let mut frame = Frame::<Chan, CH>::default();
+                for (chan, x) in frame.channels_mut().iter_mut().zip(samples) {
+                    *chan = Chan::from(x / sum);
+                }
+                frame
+            })
+            .collect::<Vec<_>>();
+        let mut audio = Audio::with_frames(hz, frames);
+        audio.tags = self.tags.clone();
+        audio
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chan::{Ch32, Channel};
+    use crate::{Audio, Frame};
+    use alloc::vec::Vec;
+
+    #[test]
+    fn stretch() {
+        let step = 2.0 * core::f32::consts::PI * 220.0 / 8_000.0;
+        let frames = (0..4_000)
+            .map(|i| {
+                Frame::<Ch32, 1>::new(Ch32::new(0.5 * (i as f32 * step).sin()))
+            })
+            .collect::<Vec<_>>();
+        let audio = Audio::with_frames(8_000, frames);
+
+        for factor in [2.0, 0.5, 1.5] {
+            let stretched = audio.stretch(factor);
+            assert_eq!(stretched.sample_rate().get(), 8_000);
+            assert_eq!(stretched.len(), (4_000.0 * factor) as usize);
+            let pitch = stretched.estimate_pitch().unwrap();
+            assert!((pitch - 220.0).abs() < 2.0, "{} {}", factor, pitch);
+
+            // The level is kept (after the fade in).
+            let peak = stretched
+                .iter()
+                .skip(100)
+                .map(|frame| frame.channels()[0].to_f32().abs())
+                .fold(0.0, f32::max);
+            assert!((peak - 0.5).abs() < 0.05, "{} {}", factor, peak);
+        }
+    }
+}
@@ -0,0 +1,174 @@
+// Copyright © 2020-2022 The Fon Contributors.
+//
+// Licensed under any of:
+// - Apache License, Version 2.0 (https://www.apache.org/licenses/LICENSE-2.0)
+// - MIT License (https://mit-license.org/)
+// - Boost Software License, Version 1.0 (https://www.boost.org/LICENSE_1_0.txt)
+// At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
+// LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
+
+//! Building audio buffers from foreign interleaved data.
+
+use crate::chan::Channel;
+use crate::error::{check_channels, AudioError};
+use crate::{Audio, Frame};
+
+use alloc::vec::Vec;
+
+/// Channel order of interleaved audio from another format.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ChannelLayout {
+    /// WAV, FLAC and SMPTE order, which is the same as `fon`'s own
+    /// (front left, front right, center, LFE, surrounds...).
+    Wav,
+    /// Vorbis and Opus order (front left, center, front right, surrounds...,
+    /// LFE last).
+    Vorbis,
+    /// AAC order (center first, then front left, front right, surrounds...,
+    /// LFE last).
+    Aac,
+}
+
+impl ChannelLayout {
+    /// Get the index in the source frame of each of `fon`'s channels, or
+    /// `None` if the layout isn't defined for `channels`.
+    fn source_order(self, channels: usize) -> Option<&'static [usize]> {
+        const IDENTITY: [usize; 12] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+
+        match (self, channels) {
+            (ChannelLayout::Wav, 1..=8 | 12) => Some(&IDENTITY[..channels]),
+            (ChannelLayout::Vorbis, 1 | 2 | 4) => Some(&IDENTITY[..channels]),
+            (ChannelLayout::Vorbis, 3) => Some(&[0, 2, 1]),
+            (ChannelLayout::Vorbis, 5) => Some(&[0, 2, 1, 3, 4]),
+            (ChannelLayout::Vorbis, 6) => Some(&[0, 2, 1, 5, 3, 4]),
+            (ChannelLayout::Vorbis, 7) => Some(&[0, 2, 1, 6, 5, 3, 4]),
+            (ChannelLayout::Vorbis, 8) => Some(&[0, 2, 1, 7, 5, 6, 3, 4]),
+            (ChannelLayout::Aac, 1 | 2) => Some(&IDENTITY[..channels]),
+            (ChannelLayout::Aac, 5) => Some(&[1, 2, 0, 3, 4]),
+            (ChannelLayout::Aac, 6) => Some(&[1, 2, 0, 5, 3, 4]),
+            (ChannelLayout::Aac, 8) => Some(&[1, 2, 0, 7, 5, 6, 3, 4]),
+            _ => None,
+        }
+    }
+}
+
+/// Builder for an [`Audio`] buffer from interleaved samples in another
+/// format's channel order, which are permuted into `fon`'s order.
+#[derive(Debug)]
+pub struct AudioBuilder<Chan: Channel, const CH: usize> {
+    hz: u32,
+    samples: Vec<Chan>,
+    layout: ChannelLayout,
+}
+
+impl<Chan: Channel, const CH: usize> AudioBuilder<Chan, CH> {
+    /// Create a new builder from interleaved samples at `hz`, in
+    /// [`ChannelLayout::Wav`] order unless set with
+    /// [`layout()`](Self::layout).
+    pub fn new<B>(hz: u32, samples: B) -> Self
+    where
+        B: Into<Vec<Chan>>,
+    {
+        Self {
+            hz,
+            samples: samples.into(),
+            layout: ChannelLayout::Wav,
+        }
+    }
+
+    /// Set the channel order of the samples.
+    pub fn layout(mut self, layout: ChannelLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Build the `Audio` buffer, failing if the sample rate is zero, the
+    /// samples don't make up whole frames, or the layout isn't defined for
+    /// `CH` channels.
+    pub fn build(self) -> Result<Audio<Chan, CH>, AudioError> {
+        if self.hz == 0 {
+            return Err(AudioError::ZeroSampleRate);
+        }
+        check_channels(self.samples.len(), CH)?;
+        let order = self
+            .layout
+            .source_order(CH)
+            .ok_or(AudioError::UnsupportedLayout { channels: CH })?;
+        let frames = self
+            .samples
+            .chunks_exact(CH)
+            .map(|samples| {
+                let mut frame = Frame::<Chan, CH>::default();
+                for (chan, &i) in frame.channels_mut().iter_mut().zip(order) {
+                    *chan = samples[i];
+                }
+                frame
+            })
+            .collect::<Vec<_>>();
+        Ok(Audio::with_frames(self.hz, frames))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chan::Ch32;
+    use crate::pos::{Front, FrontL, FrontR, Lfe, SurroundL, SurroundR};
+    use alloc::vec;
+
+    fn surround(samples: Vec<f32>, layout: ChannelLayout) -> Frame<Ch32, 6> {
+        let samples = samples.into_iter().map(Ch32::new).collect::<Vec<_>>();
+        let audio = AudioBuilder::<Ch32, 6>::new(48_000, samples)
+            .layout(layout)
+            .build()
+            .unwrap();
+        assert_eq!(audio.len(), 1);
+        audio.get(0).unwrap()
+    }
+
+    fn check(frame: Frame<Ch32, 6>) {
+        assert_eq!(frame[FrontL], Ch32::new(0.1));
+        assert_eq!(frame[FrontR], Ch32::new(0.2));
+        assert_eq!(frame[Front], Ch32::new(0.3));
+        assert_eq!(frame[Lfe], Ch32::new(0.4));
+        assert_eq!(frame[SurroundL], Ch32::new(0.5));
+        assert_eq!(frame[SurroundR], Ch32::new(0.6));
+    }
+
+    #[test]
+    fn layouts() {
+        let wav = vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6];
+        check(surround(wav, ChannelLayout::Wav));
+        let vorbis = vec![0.1, 0.3, 0.2, 0.5, 0.6, 0.4];
+        check(surround(vorbis, ChannelLayout::Vorbis));
+        let aac = vec![0.3, 0.1, 0.2, 0.5, 0.6, 0.4];
+        check(surround(aac, ChannelLayout::Aac));
+    }
+
+    #[test]
+    fn errors() {
+        let build = |hz, len, layout| {
+            AudioBuilder::<Ch32, 3>::new(hz, vec![Ch32::MID; len])
+                .layout(layout)
+                .build()
+                .map(|audio| audio.len())
+        };
+        assert_eq!(build(48_000, 6, ChannelLayout::Vorbis), Ok(2));
+        assert_eq!(
+            build(0, 6, ChannelLayout::Wav),
+            Err(AudioError::ZeroSampleRate)
+        );
+        assert_eq!(
+            build(48_000, 5, ChannelLayout::Wav),
+            Err(AudioError::ChannelCountMismatch {
+                samples: 5,
+                channels: 3
+            })
+        );
+        assert_eq!(
+            build(48_000, 6, ChannelLayout::Aac),
+            Err(AudioError::UnsupportedLayout { channels: 3 })
+        );
+    }
+}
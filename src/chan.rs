@@ -11,8 +11,7 @@
 //!
 //! An audio [`Frame`](crate::frame::Frame) is used to group multiple channels.
 
-#[cfg(not(test))]
-use crate::math::Libm;
+use_libm!();
 
 use crate::private::Sealed;
 use core::fmt::Debug;
@@ -37,10 +36,12 @@ pub trait Channel:
     + From<Ch24>
     + From<Ch32>
     + From<Ch64>
+    + From<Ch32i>
     + Into<Ch16>
     + Into<Ch24>
     + Into<Ch32>
     + Into<Ch64>
+    + Into<Ch32i>
     + Sealed
     + Unpin
     + Sized
@@ -118,6 +119,13 @@ impl From<Ch64> for Ch16 {
     }
 }
 
+impl From<Ch32i> for Ch16 {
+    #[inline(always)]
+    fn from(ch: Ch32i) -> Self {
+        Self::new((ch.0 >> 16) as i16)
+    }
+}
+
 impl From<Ch16> for i16 {
     #[inline(always)]
     fn from(ch: Ch16) -> i16 {
@@ -224,6 +232,13 @@ impl From<Ch64> for Ch24 {
     }
 }
 
+impl From<Ch32i> for Ch24 {
+    #[inline(always)]
+    fn from(ch: Ch32i) -> Self {
+        Self::new(ch.0 >> 8)
+    }
+}
+
 impl From<Ch24> for i32 {
     #[inline(always)]
     fn from(ch: Ch24) -> i32 {
@@ -322,6 +337,13 @@ impl From<Ch64> for Ch32 {
     }
 }
 
+impl From<Ch32i> for Ch32 {
+    #[inline(always)]
+    fn from(ch: Ch32i) -> Self {
+        Self::new(ch.to_f32())
+    }
+}
+
 impl From<Ch32> for f32 {
     #[inline(always)]
     fn from(ch: Ch32) -> f32 {
@@ -417,6 +439,13 @@ impl From<Ch32> for Ch64 {
     }
 }
 
+impl From<Ch32i> for Ch64 {
+    #[inline(always)]
+    fn from(ch: Ch32i) -> Self {
+        Self::new((f64::from(ch.0) + 0.5) / 2_147_483_647.5)
+    }
+}
+
 impl From<Ch64> for f64 {
     #[inline(always)]
     fn from(ch: Ch64) -> f64 {
@@ -460,6 +489,113 @@ impl Neg for Ch64 {
     }
 }
 
+/// 32-bit integer sample [Channel](Channel).
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+#[repr(transparent)]
+pub struct Ch32i(i32);
+
+impl Channel for Ch32i {
+    const MIN: Ch32i = Ch32i(-2_147_483_648);
+    const MID: Ch32i = Ch32i(0);
+    const MAX: Ch32i = Ch32i(2_147_483_647);
+
+    #[inline(always)]
+    fn to_f32(self) -> f32 {
+        ((f64::from(self.0) + 0.5) / 2_147_483_647.5) as f32
+    }
+}
+
+impl Ch32i {
+    /// Create a new 32-bit integer [`Channel`](Channel) value.
+    #[inline(always)]
+    pub const fn new(value: i32) -> Self {
+        Self(value)
+    }
+}
+
+impl From<f32> for Ch32i {
+    #[inline(always)]
+    fn from(value: f32) -> Self {
+        Self::from(Ch64::from(value))
+    }
+}
+
+impl From<Ch16> for Ch32i {
+    #[inline(always)]
+    fn from(ch: Ch16) -> Self {
+        let value = i32::from(i16::from(ch));
+        Self::new((value << 16) | (value as u16 ^ 0x8000) as i32)
+    }
+}
+
+impl From<Ch24> for Ch32i {
+    #[inline(always)]
+    fn from(ch: Ch24) -> Self {
+        let value = i32::from(ch);
+        Self::new((value << 8) | ((value >> 16) as u8 ^ 0x80) as i32)
+    }
+}
+
+impl From<Ch32> for Ch32i {
+    #[inline(always)]
+    fn from(ch: Ch32) -> Self {
+        Self::from(ch.0)
+    }
+}
+
+impl From<Ch64> for Ch32i {
+    #[inline(always)]
+    fn from(ch: Ch64) -> Self {
+        Self::new((ch.0.clamp(-1.0, 1.0) * 2_147_483_647.5).floor() as i32)
+    }
+}
+
+impl From<Ch32i> for i32 {
+    #[inline(always)]
+    fn from(ch: Ch32i) -> i32 {
+        ch.0
+    }
+}
+
+impl<R: Into<Self>> Add<R> for Ch32i {
+    type Output = Self;
+
+    #[inline(always)]
+    fn add(self, rhs: R) -> Self {
+        Self::new(i32::from(self).saturating_add(i32::from(rhs.into())))
+    }
+}
+
+impl<R: Into<Self>> Sub<R> for Ch32i {
+    type Output = Self;
+
+    #[inline(always)]
+    fn sub(self, rhs: R) -> Self {
+        Self::new(i32::from(self).saturating_sub(i32::from(rhs.into())))
+    }
+}
+
+impl<R: Into<Self>> Mul<R> for Ch32i {
+    type Output = Self;
+
+    #[inline(always)]
+    fn mul(self, rhs: R) -> Self {
+        let l = i64::from(self.0);
+        let r = i64::from(rhs.into().0);
+        let v = (l * r) / 2_147_483_647;
+        Self::new(v.clamp(-2_147_483_648, 2_147_483_647) as i32)
+    }
+}
+
+impl Neg for Ch32i {
+    type Output = Ch32i;
+
+    #[inline(always)]
+    fn neg(self) -> Self {
+        Self::new((u32::MAX - i32::from(self) as u32) as i32)
+    }
+}
+
 impl AddAssign for Ch16 {
     #[inline(always)]
     fn add_assign(&mut self, rhs: Self) {
@@ -488,6 +624,13 @@ impl AddAssign for Ch64 {
     }
 }
 
+impl AddAssign for Ch32i {
+    #[inline(always)]
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
 impl SubAssign for Ch16 {
     #[inline(always)]
     fn sub_assign(&mut self, rhs: Self) {
@@ -516,6 +659,13 @@ impl SubAssign for Ch64 {
     }
 }
 
+impl SubAssign for Ch32i {
+    #[inline(always)]
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
 impl MulAssign for Ch16 {
     #[inline(always)]
     fn mul_assign(&mut self, rhs: Self) {
@@ -544,6 +694,13 @@ impl MulAssign for Ch64 {
     }
 }
 
+impl MulAssign for Ch32i {
+    #[inline(always)]
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -614,6 +771,44 @@ mod tests {
         assert_eq!(Ch64::MAX, Ch64::from(Ch64::MAX.to_f32()));
     }
 
+    #[test]
+    fn ch32i() {
+        assert_eq!(-1.0, Ch32i::MIN.to_f32());
+        assert_eq!(1.0, Ch32i::MAX.to_f32());
+
+        assert_eq!(Ch32i::MIN, Ch32i::from(Ch32i::MIN.to_f32()));
+        assert_eq!(Ch32i::MID, Ch32i::from(Ch32i::MID.to_f32()));
+        assert_eq!(Ch32i::MAX, Ch32i::from(Ch32i::MAX.to_f32()));
+    }
+
+    #[test]
+    fn ch32i_roundtrip() {
+        assert_eq!(i32::MIN, i32::from(Ch32i::MIN));
+        assert_eq!(0, i32::from(Ch32i::MID));
+        assert_eq!(i32::MAX, i32::from(Ch32i::MAX));
+
+        // Exact through 64-bit float.
+        for value in [i32::MIN, -1, 0, 1, 123_456_789, i32::MAX] {
+            let ch = Ch32i::new(value);
+            assert_eq!(ch, Ch32i::from(Ch64::from(ch)));
+        }
+    }
+
+    #[test]
+    fn ch32i_to_ints() {
+        assert_eq!(Ch32i::MIN, Ch32i::from(Ch16::MIN));
+        assert_eq!(Ch32i::MAX, Ch32i::from(Ch16::MAX));
+        assert_eq!(Ch32i::MIN, Ch32i::from(Ch24::MIN));
+        assert_eq!(Ch32i::MAX, Ch32i::from(Ch24::MAX));
+
+        assert_eq!(Ch16::MIN, Ch16::from(Ch32i::MIN));
+        assert_eq!(Ch16::MID, Ch16::from(Ch32i::MID));
+        assert_eq!(Ch16::MAX, Ch16::from(Ch32i::MAX));
+        assert_eq!(Ch24::MIN, Ch24::from(Ch32i::MIN));
+        assert_eq!(Ch24::MID, Ch24::from(Ch32i::MID));
+        assert_eq!(Ch24::MAX, Ch24::from(Ch32i::MAX));
+    }
+
     #[test]
     fn ch16_to_ch24() {
         assert_eq!(Ch24::MIN, Ch24::from(Ch16::MIN));
@@ -753,6 +948,15 @@ mod tests {
         assert_eq!(Ch24::MIN, Ch24::new(-4194304) - Ch24::new(6291456));
     }
 
+    #[test]
+    fn ch32i_saturation() {
+        assert_eq!(Ch32i::MAX, Ch32i::new(1 << 30) + Ch32i::new(i32::MAX));
+        assert_eq!(Ch32i::MIN, Ch32i::new(-(1 << 30)) - Ch32i::new(i32::MAX));
+        assert_eq!(Ch32i::MIN, -Ch32i::MAX);
+        assert_eq!(Ch32i::MAX, Ch32i::MAX * Ch32i::MAX);
+        assert_eq!(Ch32i::new(-(1 << 30)), Ch32i::MAX * Ch32i::new(-(1 << 30)));
+    }
+
     #[test]
     fn ch32_unsaturation() {
         assert_eq!(Ch32::new(1.25), Ch32::new(0.75) + Ch32::new(0.5));
@@ -0,0 +1,119 @@
+// Copyright © 2020-2022 The Fon Contributors.
+//
+// Licensed under any of:
+// - Apache License, Version 2.0 (https://www.apache.org/licenses/LICENSE-2.0)
+// - MIT License (https://mit-license.org/)
+// - Boost Software License, Version 1.0 (https://www.boost.org/LICENSE_1_0.txt)
+// At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
+// LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
+
+//! Conversions to and from `dasp` frames (requires the `dasp` feature).
+
+use crate::chan::Channel;
+use crate::frame::Frame;
+use crate::Audio;
+
+use ::dasp::sample::{FromSample, ToSample};
+use ::dasp::Frame as DaspFrame;
+use alloc::vec::Vec;
+
+impl<Chan: Channel, const CH: usize> Frame<Chan, CH> {
+    /// Convert to a `dasp` frame with the same number of channels, such as
+    /// `[f32; CH]` (requires the `dasp` feature).
+    ///
+    /// # Panics
+    /// If the `dasp` frame doesn't have `CH` channels.
+    pub fn to_dasp<F>(self) -> F
+    where
+        F: DaspFrame,
+        F::Sample: FromSample<f32>,
+    {
+        assert_eq!(F::CHANNELS, CH);
+        F::from_fn(|chan| {
+            F::Sample::from_sample_(self.channels()[chan].to_f32())
+        })
+    }
+
+    /// Convert from a `dasp` frame with the same number of channels
+    /// (requires the `dasp` feature).
+    ///
+    /// # Panics
+    /// If the `dasp` frame doesn't have `CH` channels.
+    pub fn from_dasp<F>(frame: F) -> Self
+    where
+        F: DaspFrame,
+        F::Sample: ToSample<f32>,
+    {
+        assert_eq!(F::CHANNELS, CH);
+        let mut out = Self::default();
+        for (chan, sample) in
+            out.channels_mut().iter_mut().zip(frame.channels())
+        {
+            *chan = Chan::from(sample.to_sample_());
+        }
+        out
+    }
+}
+
+impl<Chan: Channel, const CH: usize> Audio<Chan, CH> {
+    /// Iterate over the audio as `dasp` frames, which can be turned into a
+    /// `dasp` signal with `dasp::signal::from_iter()` (requires the `dasp`
+    /// feature).
+    ///
+    /// # Panics
+    /// If the `dasp` frame doesn't have `CH` channels.
+    pub fn to_dasp_frames<F>(&self) -> impl Iterator<Item = F> + '_
+    where
+        F: DaspFrame,
+        F::Sample: FromSample<f32>,
+    {
+        self.iter().map(|frame| frame.to_dasp())
+    }
+
+    /// Construct an `Audio` buffer from `dasp` frames, such as those taken
+    /// from a `dasp` signal (requires the `dasp` feature).
+    ///
+    /// # Panics
+    /// If the `dasp` frames don't have `CH` channels.
+    pub fn from_dasp_frames<F, I>(hz: u32, frames: I) -> Self
+    where
+        F: DaspFrame,
+        F::Sample: ToSample<f32>,
+        I: IntoIterator<Item = F>,
+    {
+        let frames =
+            frames.into_iter().map(Frame::from_dasp).collect::<Vec<_>>();
+        Self::with_frames(hz, frames)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chan::{Ch16, Ch32};
+    use crate::{Audio, Frame};
+    use alloc::vec::Vec;
+
+    #[test]
+    fn frame_roundtrip() {
+        let frame = Frame::<Ch32, 2>::new(Ch32::new(0.25), Ch32::new(-0.75));
+        let dasp: [f32; 2] = frame.to_dasp();
+        assert_eq!(dasp, [0.25, -0.75]);
+        assert_eq!(Frame::<Ch32, 2>::from_dasp(dasp), frame);
+
+        let frame = Frame::<Ch16, 2>::new(Ch16::new(i16::MIN), Ch16::new(1));
+        let dasp: [f64; 2] = frame.to_dasp();
+        assert_eq!(Frame::<Ch16, 2>::from_dasp(dasp), frame);
+    }
+
+    #[test]
+    fn audio_roundtrip() {
+        let audio = Audio::<Ch32, 2>::with_f32_buffer(
+            48_000,
+            [0.0, 0.5, -0.5, 1.0, 0.125, -1.0],
+        );
+        let frames = audio.to_dasp_frames::<[f32; 2]>().collect::<Vec<_>>();
+        assert_eq!(frames[1], [-0.5, 1.0]);
+        let copy = Audio::<Ch32, 2>::from_dasp_frames(48_000, frames);
+        assert_eq!(copy.as_slice(), audio.as_slice());
+    }
+}
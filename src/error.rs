@@ -0,0 +1,81 @@
+// Copyright © 2020-2022 The Fon Contributors.
+//
+// Licensed under any of:
+// - Apache License, Version 2.0 (https://www.apache.org/licenses/LICENSE-2.0)
+// - MIT License (https://mit-license.org/)
+// - Boost Software License, Version 1.0 (https://www.boost.org/LICENSE_1_0.txt)
+// At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
+// LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
+
+use core::fmt;
+
+/// Error constructing an [`Audio`](crate::Audio) buffer.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AudioError {
+    /// The number of interleaved samples isn't a multiple of the channel
+    /// count, so the buffer doesn't contain whole frames.
+    ChannelCountMismatch {
+        /// Number of samples in the buffer.
+        samples: usize,
+        /// Number of channels in each frame.
+        channels: usize,
+    },
+    /// The sample rate is zero.
+    ZeroSampleRate,
+    /// The source channel layout has no mapping for this channel count.
+    UnsupportedLayout {
+        /// Number of channels in each frame.
+        channels: usize,
+    },
+    /// The encoded audio is malformed or uses an unsupported format.
+    Malformed(&'static str),
+}
+
+impl fmt::Display for AudioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AudioError::ChannelCountMismatch { samples, channels } => write!(
+                f,
+                "{} interleaved samples can't be split into {}-channel frames \
+                 ({} left over)",
+                samples,
+                channels,
+                samples % channels,
+            ),
+            AudioError::ZeroSampleRate => write!(f, "sample rate is zero"),
+            AudioError::UnsupportedLayout { channels } => write!(
+                f,
+                "channel layout doesn't support {} channels",
+                channels,
+            ),
+            AudioError::Malformed(reason) => {
+                write!(f, "malformed audio data: {}", reason)
+            }
+        }
+    }
+}
+
+/// Check that `samples` interleaved samples make up whole frames.
+pub(crate) fn check_channels(
+    samples: usize,
+    channels: usize,
+) -> Result<(), AudioError> {
+    if samples % channels == 0 {
+        Ok(())
+    } else {
+        Err(AudioError::ChannelCountMismatch { samples, channels })
+    }
+}
+
+/// Check that `bytes` bytes of interleaved 3-byte samples make up whole
+/// frames.
+pub(crate) fn check_u8_buffer(
+    bytes: usize,
+    channels: usize,
+) -> Result<(), AudioError> {
+    if bytes % 3 != 0 {
+        return Err(AudioError::Malformed("partial 24-bit sample"));
+    }
+    check_channels(bytes / 3, channels)
+}
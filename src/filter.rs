@@ -0,0 +1,198 @@
+// Copyright © 2020-2022 The Fon Contributors.
+//
+// Licensed under any of:
+// - Apache License, Version 2.0 (https://www.apache.org/licenses/LICENSE-2.0)
+// - MIT License (https://mit-license.org/)
+// - Boost Software License, Version 1.0 (https://www.boost.org/LICENSE_1_0.txt)
+// At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
+// LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
+
+//! Stateful filters for streaming audio.
+
+use alloc::{vec, vec::Vec};
+use core::marker::PhantomData;
+
+use crate::chan::Channel;
+use crate::{Audio, Frame};
+
+/// One-pole DC-blocking filter, for removing DC offset from audio as it
+/// streams in (without needing the whole buffer to find the mean).
+///
+/// Each channel is filtered with `y[n] = x[n] - x[n-1] + R * y[n-1]`.
+#[derive(Copy, Clone, Debug)]
+pub struct DcBlocker<Chan: Channel, const CH: usize> {
+    r: f32,
+    x1: [f32; CH],
+    y1: [f32; CH],
+    _phantom: PhantomData<Chan>,
+}
+
+impl<Chan: Channel, const CH: usize> Default for DcBlocker<Chan, CH> {
+    fn default() -> Self {
+        Self::new(0.995)
+    }
+}
+
+impl<Chan: Channel, const CH: usize> DcBlocker<Chan, CH> {
+    /// Create a new DC blocker with pole `r` (typically just under 1.0).
+    ///
+    /// The closer `r` is to 1.0, the lower the cutoff frequency, and the
+    /// slower DC offset is removed.
+    ///
+    /// # Panics
+    /// If `r` is not in the range `0.0..1.0`.
+    pub fn new(r: f32) -> Self {
+        assert!((0.0..1.0).contains(&r));
+        Self {
+            r,
+            x1: [0.0; CH],
+            y1: [0.0; CH],
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Filter a single frame.
+    pub fn process_frame(&mut self, frame: Frame<Chan, CH>) -> Frame<Chan, CH> {
+        let mut out = Frame::<Chan, CH>::default();
+        for (i, (out, chan)) in out
+            .channels_mut()
+            .iter_mut()
+            .zip(frame.channels())
+            .enumerate()
+        {
+            let x = chan.to_f32();
+            let y = x - self.x1[i] + self.r * self.y1[i];
+            self.x1[i] = x;
+            self.y1[i] = y;
+            *out = Chan::from(y);
+        }
+        out
+    }
+
+    /// Filter a buffer of audio in place, continuing from previous frames.
+    pub fn process(&mut self, audio: &mut Audio<Chan, CH>) {
+        for frame in audio.iter_mut() {
+            *frame = self.process_frame(*frame);
+        }
+    }
+}
+
+/// Finite impulse response filter, convolving each channel with a kernel of
+/// `taps` (direct convolution).
+///
+/// Each channel is filtered with the following, keeping enough past frames to
+/// continue across buffers:
+///
+/// ```text
+/// y[n] = taps[0] * x[n] + taps[1] * x[n-1] + ...
+/// ```
+#[derive(Clone, Debug)]
+pub struct FirFilter<Chan: Channel, const CH: usize> {
+    taps: Vec<f32>,
+    /// Ring buffer of the last `taps.len()` input frames.
+    history: Vec<[f32; CH]>,
+    /// Index of the most recent frame in `history`.
+    index: usize,
+    _phantom: PhantomData<Chan>,
+}
+
+impl<Chan: Channel, const CH: usize> FirFilter<Chan, CH> {
+    /// Create a new FIR filter from its kernel.
+    ///
+    /// # Panics
+    /// If `taps` is empty.
+    pub fn new(taps: &[f32]) -> Self {
+        assert!(!taps.is_empty());
+        Self {
+            taps: taps.to_vec(),
+            history: vec![[0.0; CH]; taps.len()],
+            index: 0,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Filter a single frame.
+    pub fn process_frame(&mut self, frame: Frame<Chan, CH>) -> Frame<Chan, CH> {
+        let len = self.taps.len();
+        self.index = (self.index + 1) % len;
+        for (x, chan) in
+            self.history[self.index].iter_mut().zip(frame.channels())
+        {
+            *x = chan.to_f32();
+        }
+
+        let mut sum = [0.0f32; CH];
+        for (k, tap) in self.taps.iter().enumerate() {
+            let past = &self.history[(self.index + len - k) % len];
+            for (sum, x) in sum.iter_mut().zip(past) {
+                *sum += tap * x;
+            }
+        }
+        let mut out = Frame::<Chan, CH>::default();
+        for (out, sum) in out.channels_mut().iter_mut().zip(sum.iter()) {
+            *out = Chan::from(*sum);
+        }
+        out
+    }
+
+    /// Filter a buffer of audio in place, continuing from previous frames.
+    pub fn process(&mut self, audio: &mut Audio<Chan, CH>) {
+        for frame in audio.iter_mut() {
+            *frame = self.process_frame(*frame);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chan::Ch32;
+
+    #[test]
+    fn dc_blocker() {
+        let mut blocker = DcBlocker::<Ch32, 2>::default();
+        let step = Frame::<Ch32, 2>::new(Ch32::new(0.5), Ch32::new(-0.25));
+
+        // The step passes through at first.
+        let first = blocker.process_frame(step);
+        assert_eq!(first, step);
+
+        // Then decays towards zero.
+        let mut last = 0.5;
+        for _ in 0..2000 {
+            let out = blocker.process_frame(step).channels()[0].to_f32();
+            assert!(out < last);
+            assert!(out > 0.0);
+            last = out;
+        }
+        assert!(last < 0.001);
+
+        // Until DC offset is removed.
+        let mut audio = Audio::<Ch32, 2>::with_frames(48_000, [step; 1000]);
+        blocker.process(&mut audio);
+        for frame in audio.iter() {
+            for chan in frame.channels() {
+                assert!(chan.to_f32().abs() < 0.0001);
+            }
+        }
+    }
+
+    #[test]
+    fn fir_filter() {
+        // Two-point moving average, continuing across buffers.
+        let ramp = (0..8).map(|i| i as f32 / 8.0).collect::<Vec<_>>();
+        let mut filter = FirFilter::new(&[0.5, 0.5]);
+        let (mut a, mut b) = (
+            Audio::<Ch32, 1>::with_f32_buffer(48_000, &ramp[..3]),
+            Audio::<Ch32, 1>::with_f32_buffer(48_000, &ramp[3..]),
+        );
+        filter.process(&mut a);
+        filter.process(&mut b);
+        let averaged =
+            a.iter().chain(b.iter()).map(|f| f.channels()[0].to_f32());
+        for (i, y) in averaged.enumerate() {
+            let prev = if i == 0 { 0.0 } else { ramp[i - 1] };
+            assert_eq!(y, 0.5 * ramp[i] + 0.5 * prev);
+        }
+    }
+}
@@ -9,14 +9,66 @@
 
 //! Frame (interleaved sample) types
 
-#[cfg(not(test))]
-use crate::math::Libm;
+use_libm!();
 
 use crate::chan::Channel;
-use core::f32::consts::FRAC_PI_2;
+use crate::error::AudioError;
+use core::f32::consts::{FRAC_1_SQRT_2, FRAC_PI_2};
 use core::fmt::Debug;
 use core::ops::{Add, Mul, Neg, Sub};
 
+/// Pan law used when panning a channel between two speakers.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PanLaw {
+    /// Gain changes linearly between speakers (-6 dB at the center).
+    Linear,
+    /// Total power is kept constant between speakers (-3 dB at the center).
+    ConstantPower,
+    /// Compromise between linear and constant power (-4.5 dB at the center).
+    Balanced,
+}
+
+impl PanLaw {
+    /// Get the gains of the near and far speakers, where `x` is the distance
+    /// from the near speaker in radians (0 to π/2).
+    #[inline(always)]
+    fn gains(self, x: f32) -> (f32, f32) {
+        match self {
+            PanLaw::Linear => {
+                let p = x / FRAC_PI_2;
+                (1.0 - p, p)
+            }
+            PanLaw::ConstantPower => (x.cos(), x.sin()),
+            PanLaw::Balanced => {
+                let p = x / FRAC_PI_2;
+                (
+                    ((1.0 - p) * x.cos()).max(0.0).sqrt(),
+                    (p * x.sin()).max(0.0).sqrt(),
+                )
+            }
+        }
+    }
+}
+
+/// Operation used to blend one frame onto another, as when compositing
+/// layers of audio.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Blend {
+    /// Sum the channels (clipping at full scale).
+    Add,
+    /// Multiply the channels, as for ring modulation.
+    Multiply,
+    /// Keep the lesser of the two channels.
+    Min,
+    /// Keep the greater of the two channels.
+    Max,
+    /// Replace the channels with the other frame's.
+    Overwrite,
+    /// Sum the channels, softly saturating towards full scale when both have
+    /// the same sign (`a + b - a × b` on the magnitudes), so it never clips.
+    Screen,
+}
+
 /// Frame - A number of interleaved sample [channel]s.
 ///
 /// [channel]: crate::chan::Channel
@@ -30,6 +82,52 @@ impl<Chan: Channel, const CH: usize> Default for Frame<Chan, CH> {
     }
 }
 
+/// Frame with a channel count chosen at runtime (from 1 to 8, or 12), as
+/// returned by [`Frame::to_dyn()`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DynFrame<Chan: Channel> {
+    channels: [Chan; 12],
+    len: usize,
+}
+
+impl<Chan: Channel> DynFrame<Chan> {
+    /// Get immutable access to the channels.
+    #[inline(always)]
+    pub fn channels(&self) -> &[Chan] {
+        &self.channels[..self.len]
+    }
+
+    /// Get a mutable reference to the channels.
+    #[inline(always)]
+    pub fn channels_mut(&mut self) -> &mut [Chan] {
+        &mut self.channels[..self.len]
+    }
+}
+
+impl<Chan: Channel> DynFrame<Chan> {
+    /// Copy the channels of a frame with at most 12 channels.
+    fn from_frame<const CH: usize>(frame: Frame<Chan, CH>) -> Self {
+        let mut channels = [Chan::default(); 12];
+        channels[..CH].copy_from_slice(&frame.0);
+        Self { channels, len: CH }
+    }
+}
+
+impl<Chan: Channel, const CH: usize> TryFrom<Frame<Chan, CH>>
+    for DynFrame<Chan>
+{
+    type Error = AudioError;
+
+    /// Fails with [`AudioError::UnsupportedLayout`] for more than 12
+    /// channels.
+    fn try_from(frame: Frame<Chan, CH>) -> Result<Self, AudioError> {
+        if CH > 12 {
+            return Err(AudioError::UnsupportedLayout { channels: CH });
+        }
+        Ok(Self::from_frame(frame))
+    }
+}
+
 impl<Chan: Channel, const CH: usize> Frame<Chan, CH> {
     /// Get a mutable slice of the channels in this frame.
     #[inline(always)]
@@ -49,15 +147,32 @@ impl<Chan: Channel, const CH: usize> Frame<Chan, CH> {
     /// The algorithm used is "Constant Power Panning".
     #[inline(always)]
     pub fn pan<C: Channel + Into<Chan>>(self, channel: C, angle: f32) -> Self {
+        self.pan_with(channel, angle, PanLaw::ConstantPower)
+    }
+
+    /// Mix a panned channel into this audio frame, using the selected
+    /// [`PanLaw`](crate::PanLaw).
+    ///
+    /// 1.0/0.0 is straight ahead, 0.25 is right, 0.5 is back, and 0.75 is left.
+    #[inline(always)]
+    pub fn pan_with<C: Channel + Into<Chan>>(
+        self,
+        channel: C,
+        angle: f32,
+        law: PanLaw,
+    ) -> Self {
+        let angle = angle.rem_euclid(1.0);
         match CH {
-            1 => self.pan_1(channel.into(), angle.rem_euclid(1.0)),
-            2 => self.pan_2(channel.into(), angle.rem_euclid(1.0)),
-            3 => self.pan_3(channel.into(), angle.rem_euclid(1.0)),
-            4 => self.pan_4(channel.into(), angle.rem_euclid(1.0)),
-            5 => self.pan_5(channel.into(), angle.rem_euclid(1.0)),
-            6 => self.pan_6(channel.into(), angle.rem_euclid(1.0)),
-            7 => self.pan_7(channel.into(), angle.rem_euclid(1.0)),
-            8 => self.pan_8(channel.into(), angle.rem_euclid(1.0)),
+            1 => self.pan_1(channel.into(), angle),
+            2 => self.pan_2(channel.into(), angle, law),
+            3 => self.pan_3(channel.into(), angle, law),
+            4 => self.pan_4(channel.into(), angle, law),
+            5 => self.pan_5(channel.into(), angle, law),
+            6 => self.pan_6(channel.into(), angle, law),
+            7 => self.pan_7(channel.into(), angle, law),
+            8 => self.pan_8(channel.into(), angle, law),
+            // Horizontal panning only uses the 7.1 bed of a 7.1.4 frame.
+            12 => self.pan_8(channel.into(), angle, law),
             _ => unreachable!(),
         }
     }
@@ -71,6 +186,114 @@ impl<Chan: Channel, const CH: usize> Frame<Chan, CH> {
         }
     }
 
+    /// Get the absolute value of each channel.
+    #[inline(always)]
+    pub fn abs(mut self) -> Self {
+        for x in self.0.iter_mut() {
+            *x = x.to_f32().abs().into();
+        }
+        self
+    }
+
+    /// Silence each channel whose absolute value is below `threshold` (a
+    /// hard noise gate on individual samples).
+    #[inline(always)]
+    pub fn gate(mut self, threshold: f32) -> Self {
+        for x in self.0.iter_mut() {
+            if x.to_f32().abs() < threshold {
+                *x = Chan::MID;
+            }
+        }
+        self
+    }
+
+    /// Get the index and value of the loudest channel (with the greatest
+    /// absolute value).  Ties go to the lowest index.
+    #[inline(always)]
+    pub fn max_channel(&self) -> (usize, Chan) {
+        self.find_channel(|level, best| level > best)
+    }
+
+    /// Get the index and value of the quietest channel (with the least
+    /// absolute value).  Ties go to the lowest index.
+    #[inline(always)]
+    pub fn min_channel(&self) -> (usize, Chan) {
+        self.find_channel(|level, best| level < best)
+    }
+
+    /// Get the absolute value of the loudest channel.
+    #[inline(always)]
+    pub fn peak_channel_abs(&self) -> f32 {
+        self.max_channel().1.to_f32().abs()
+    }
+
+    /// Find the channel whose level `beats` the levels of all of the others.
+    #[inline(always)]
+    fn find_channel(&self, beats: impl Fn(f32, f32) -> bool) -> (usize, Chan) {
+        let mut best = 0;
+        for (i, x) in self.0.iter().enumerate().skip(1) {
+            if beats(x.to_f32().abs(), self.0[best].to_f32().abs()) {
+                best = i;
+            }
+        }
+        (best, self.0[best])
+    }
+
+    /// Blend `other` onto this frame, channel by channel, with `op`.
+    #[inline(always)]
+    pub fn blend(self, other: Self, op: Blend) -> Self {
+        match op {
+            Blend::Add => self + other,
+            Blend::Multiply => self * other,
+            Blend::Overwrite => other,
+            Blend::Min | Blend::Max | Blend::Screen => {
+                let mut out = self;
+                for (a, b) in out.0.iter_mut().zip(other.0.iter()) {
+                    *a = match op {
+                        Blend::Min if *b < *a => *b,
+                        Blend::Max if *b > *a => *b,
+                        Blend::Screen => {
+                            let (x, y) = (a.to_f32(), b.to_f32());
+                            if x * y > 0.0 {
+                                x + y - x * y.abs()
+                            } else {
+                                x + y
+                            }
+                            .into()
+                        }
+                        _ => *a,
+                    };
+                }
+                out
+            }
+        }
+    }
+
+    /// Get the sign of each channel: 1.0 if positive, -1.0 if negative, or 0.0
+    /// if zero.
+    #[inline(always)]
+    pub fn signum(mut self) -> Self {
+        for x in self.0.iter_mut() {
+            *x = if *x > Chan::MID {
+                Chan::MAX
+            } else if *x < Chan::MID {
+                Chan::MIN
+            } else {
+                Chan::MID
+            };
+        }
+        self
+    }
+
+    /// Apply a separate gain to each channel.  This function may introduce
+    /// hard clipping distortion if any of the `gains` are greater than 1.
+    #[inline(always)]
+    pub fn gain_each(&mut self, gains: [f32; CH]) {
+        for (x, gain) in self.0.iter_mut().zip(gains.iter()) {
+            *x = (x.to_f32() * gain).into();
+        }
+    }
+
     /// Apply linear interpolation with another frame.
     #[inline(always)]
     pub fn lerp(&mut self, rhs: Self, t: f32) {
@@ -79,6 +302,141 @@ impl<Chan: Channel, const CH: usize> Frame<Chan, CH> {
         }
     }
 
+    /// Interpolate with another frame by the direction of the sound, rather
+    /// than channel by channel like [`lerp()`](Self::lerp).
+    ///
+    /// Each frame's directional channels are treated as one panned source,
+    /// whose angle and amplitude are interpolated (the shortest way around)
+    /// and panned again, so a crossfade between two directions moves the
+    /// source smoothly instead of playing it from both at half level.  The
+    /// LFE and height channels are interpolated linearly, as are mono and
+    /// stereo frames.
+    pub fn lerp_spatial(&mut self, rhs: Self, t: f32) {
+        let speakers = Self::speakers();
+        if speakers.is_empty() {
+            self.lerp(rhs, t);
+            return;
+        }
+
+        let (a_amp, a_angle) = self.source(speakers);
+        let (b_amp, b_angle) = rhs.source(speakers);
+        let (a_angle, b_angle) = match (a_amp == 0.0, b_amp == 0.0) {
+            (true, _) => (b_angle, b_angle),
+            (_, true) => (a_angle, a_angle),
+            _ => (a_angle, b_angle),
+        };
+        let turn = (b_angle - a_angle + 0.5).rem_euclid(1.0) - 0.5;
+        let amp = a_amp + (b_amp - a_amp) * t;
+        let mut out = Self::default().pan(Chan::from(amp), a_angle + turn * t);
+
+        // Non-directional channels.
+        for chan in 0..CH {
+            if speakers.iter().all(|(index, _)| *index != chan) {
+                out.0[chan] = self.0[chan].lerp(rhs.0[chan], t.into());
+            }
+        }
+        *self = out;
+    }
+
+    /// Get the directional channels (and their angles), in order clockwise
+    /// from the front, as positioned by `pan()`.
+    fn speakers() -> &'static [(usize, f32)] {
+        match CH {
+            3 => &[(2, 0.0), (1, 0.25), (2, 0.5), (0, 0.75)],
+            4 => &[
+                (1, 30.0 / 360.0),
+                (3, 110.0 / 360.0),
+                (2, 250.0 / 360.0),
+                (0, 330.0 / 360.0),
+            ],
+            5 => &[
+                (2, 0.0),
+                (1, 30.0 / 360.0),
+                (4, 110.0 / 360.0),
+                (3, 250.0 / 360.0),
+                (0, 330.0 / 360.0),
+            ],
+            6 => &[
+                (2, 0.0),
+                (1, 30.0 / 360.0),
+                (5, 110.0 / 360.0),
+                (4, 250.0 / 360.0),
+                (0, 330.0 / 360.0),
+            ],
+            7 => &[
+                (2, 0.0),
+                (1, 30.0 / 360.0),
+                (6, 90.0 / 360.0),
+                (4, 180.0 / 360.0),
+                (5, 270.0 / 360.0),
+                (0, 330.0 / 360.0),
+            ],
+            8 | 12 => &[
+                (2, 0.0),
+                (1, 30.0 / 360.0),
+                (7, 90.0 / 360.0),
+                (5, 150.0 / 360.0),
+                (4, 210.0 / 360.0),
+                (6, 270.0 / 360.0),
+                (0, 330.0 / 360.0),
+            ],
+            _ => &[],
+        }
+    }
+
+    /// Find the signed amplitude and angle of the source that `pan()` would
+    /// have panned to get the directional channels of this frame.
+    fn source(&self, speakers: &[(usize, f32)]) -> (f32, f32) {
+        // The source is between the loudest pair of adjacent speakers.
+        let len = speakers.len();
+        let power = |i: usize| self.0[speakers[i % len].0].to_f32().powi(2);
+        let near = (0..len)
+            .max_by(|&a, &b| {
+                let a = power(a) + power(a + 1);
+                let b = power(b) + power(b + 1);
+                a.partial_cmp(&b).unwrap_or(core::cmp::Ordering::Equal)
+            })
+            .unwrap_or(0);
+        let (near_chan, near_angle) = speakers[near];
+        let (far_chan, far_angle) = speakers[(near + 1) % len];
+        let pair = power(near) + power(near + 1);
+        if pair == 0.0 {
+            return (0.0, 0.0);
+        }
+
+        // Invert the constant power pan law.
+        let x = (self.0[far_chan].to_f32().abs() / pair.sqrt()).asin();
+        let slice = (far_angle - near_angle).rem_euclid(1.0);
+        let angle = near_angle + slice * x / FRAC_PI_2;
+        let loudest = if power(near) >= power(near + 1) {
+            near_chan
+        } else {
+            far_chan
+        };
+        let total = speakers.iter().map(|(i, _)| self.0[*i].to_f32().powi(2));
+        let amp = total.sum::<f32>().sqrt();
+        if self.0[loudest].to_f32() < 0.0 {
+            (-amp, angle)
+        } else {
+            (amp, angle)
+        }
+    }
+
+    /// Zero any channels holding subnormal values.
+    ///
+    /// Decaying feedback (as in reverb and echo effects) eventually reaches
+    /// subnormal floats, which are very slow to process on some CPUs.  Call
+    /// this inside effect loops to avoid the penalty.  Values are checked at
+    /// 32-bit float precision, and integer channels are never subnormal.
+    #[inline(always)]
+    pub fn flush_subnormals(&mut self) {
+        for x in self.0.iter_mut() {
+            if x.to_f32().is_subnormal() {
+                *x = Chan::MID;
+            }
+        }
+    }
+
     /// Convert an audio Frame to another format.
     #[inline(always)]
     pub fn to<C: Channel + From<Chan>, const N: usize>(self) -> Frame<C, N> {
@@ -91,10 +449,32 @@ impl<Chan: Channel, const CH: usize> Frame<Chan, CH> {
             6 => self.to_6(),
             7 => self.to_7(),
             8 => self.to_8(),
+            12 => self.to_12(),
             _ => unreachable!(),
         }
     }
 
+    /// Convert an audio Frame to a channel count only known at runtime (for
+    /// instance, to match an output device).
+    ///
+    /// # Panics
+    /// If `out_channels` is not from 1 to 8, or 12.
+    #[inline(always)]
+    pub fn to_dyn(self, out_channels: usize) -> DynFrame<Chan> {
+        match out_channels {
+            1 => DynFrame::from_frame(self.to::<Chan, 1>()),
+            2 => DynFrame::from_frame(self.to::<Chan, 2>()),
+            3 => DynFrame::from_frame(self.to::<Chan, 3>()),
+            4 => DynFrame::from_frame(self.to::<Chan, 4>()),
+            5 => DynFrame::from_frame(self.to::<Chan, 5>()),
+            6 => DynFrame::from_frame(self.to::<Chan, 6>()),
+            7 => DynFrame::from_frame(self.to::<Chan, 7>()),
+            8 => DynFrame::from_frame(self.to::<Chan, 8>()),
+            12 => DynFrame::from_frame(self.to::<Chan, 12>()),
+            _ => panic!("unsupported channel count {}", out_channels),
+        }
+    }
+
     #[inline(always)]
     fn pan_1(mut self, chan: Chan, _x: f32) -> Self {
         const MONO: usize = 0;
@@ -105,21 +485,27 @@ impl<Chan: Channel, const CH: usize> Frame<Chan, CH> {
     }
 
     #[inline(always)]
-    fn pan_2(mut self, chan: Chan, x: f32) -> Self {
+    fn pan_2(mut self, chan: Chan, x: f32, law: PanLaw) -> Self {
         const LEFT: usize = 0;
         const RIGHT: usize = 1;
 
         // Convert to radians, left is now at 0.
         let x = (x + 0.25) * core::f32::consts::PI;
-        // Pan distance
-        self.0[LEFT] += chan * x.cos().into();
-        self.0[RIGHT] += chan * x.sin().into();
+        // Pan distance, rotating a quarter turn at a time past right.
+        let (near, far) = law.gains(x % FRAC_PI_2);
+        let (left, right) = match (x / FRAC_PI_2) as u32 {
+            0 => (near, far),
+            1 => (-far, near),
+            _ => (-near, -far),
+        };
+        self.0[LEFT] += chan * left.into();
+        self.0[RIGHT] += chan * right.into();
 
         self
     }
 
     #[inline(always)]
-    fn pan_3(mut self, chan: Chan, x: f32) -> Self {
+    fn pan_3(mut self, chan: Chan, x: f32, law: PanLaw) -> Self {
         const LEFT: usize = 0;
         const RIGHT: usize = 1;
         const CENTER: usize = 2;
@@ -129,26 +515,30 @@ impl<Chan: Channel, const CH: usize> Frame<Chan, CH> {
             // Center-Right Speakers
             x if x < 0.25 => {
                 let x = 4.0 * x * FRAC_PI_2;
-                self.0[CENTER] += chan * x.cos().into();
-                self.0[RIGHT] += chan * x.sin().into();
+                let (near, far) = law.gains(x);
+                self.0[CENTER] += chan * near.into();
+                self.0[RIGHT] += chan * far.into();
             }
             // Right-Center Speakers
             x if x < 0.5 => {
                 let x = 4.0 * (x - 0.25) * FRAC_PI_2;
-                self.0[RIGHT] += chan * x.cos().into();
-                self.0[CENTER] += chan * x.sin().into();
+                let (near, far) = law.gains(x);
+                self.0[RIGHT] += chan * near.into();
+                self.0[CENTER] += chan * far.into();
             }
             // Center-Left Speakers
             x if x < 0.75 => {
                 let x = 4.0 * (x - 0.50) * FRAC_PI_2;
-                self.0[CENTER] += chan * x.cos().into();
-                self.0[LEFT] += chan * x.sin().into();
+                let (near, far) = law.gains(x);
+                self.0[CENTER] += chan * near.into();
+                self.0[LEFT] += chan * far.into();
             }
             // Left-Center Speakers
             x => {
                 let x = 4.0 * (x - 0.75) * FRAC_PI_2;
-                self.0[LEFT] += chan * x.cos().into();
-                self.0[CENTER] += chan * x.sin().into();
+                let (near, far) = law.gains(x);
+                self.0[LEFT] += chan * near.into();
+                self.0[CENTER] += chan * far.into();
             }
         }
 
@@ -156,7 +546,7 @@ impl<Chan: Channel, const CH: usize> Frame<Chan, CH> {
     }
 
     #[inline(always)]
-    fn pan_4(mut self, chan: Chan, x: f32) -> Self {
+    fn pan_4(mut self, chan: Chan, x: f32, law: PanLaw) -> Self {
         const FRONT_L: usize = 0;
         const FRONT_R: usize = 1;
         const SURROUND_L: usize = 2;
@@ -167,26 +557,30 @@ impl<Chan: Channel, const CH: usize> Frame<Chan, CH> {
             // Front Left - Front Right Speakers (60° slice)
             x if x < 60.0 / 360.0 => {
                 let x = (360.0 / 60.0) * x * FRAC_PI_2;
-                self.0[FRONT_L] += chan * x.cos().into();
-                self.0[FRONT_R] += chan * x.sin().into();
+                let (near, far) = law.gains(x);
+                self.0[FRONT_L] += chan * near.into();
+                self.0[FRONT_R] += chan * far.into();
             }
             // Front Right - Back Right Speakers (80° slice)
             x if x < 140.0 / 360.0 => {
                 let x = (360.0 / 80.0) * (x - 60.0 / 360.0) * FRAC_PI_2;
-                self.0[FRONT_R] += chan * x.cos().into();
-                self.0[SURROUND_R] += chan * x.sin().into();
+                let (near, far) = law.gains(x);
+                self.0[FRONT_R] += chan * near.into();
+                self.0[SURROUND_R] += chan * far.into();
             }
             // Back Right - Back Left Speakers (140° slice)
             x if x < 280.0 / 360.0 => {
                 let x = (360.0 / 140.0) * (x - 140.0 / 360.0) * FRAC_PI_2;
-                self.0[SURROUND_R] += chan * x.cos().into();
-                self.0[SURROUND_L] += chan * x.sin().into();
+                let (near, far) = law.gains(x);
+                self.0[SURROUND_R] += chan * near.into();
+                self.0[SURROUND_L] += chan * far.into();
             }
             // Back Left - Front Left Speakers (80° slice)
             x => {
                 let x = (360.0 / 80.0) * (x - 280.0 / 360.0) * FRAC_PI_2;
-                self.0[SURROUND_L] += chan * x.cos().into();
-                self.0[FRONT_L] += chan * x.sin().into();
+                let (near, far) = law.gains(x);
+                self.0[SURROUND_L] += chan * near.into();
+                self.0[FRONT_L] += chan * far.into();
             }
         }
 
@@ -194,7 +588,7 @@ impl<Chan: Channel, const CH: usize> Frame<Chan, CH> {
     }
 
     #[inline(always)]
-    fn pan_5(mut self, chan: Chan, x: f32) -> Self {
+    fn pan_5(mut self, chan: Chan, x: f32, law: PanLaw) -> Self {
         const FRONT_L: usize = 0;
         const FRONT_R: usize = 1;
         const FRONT: usize = 2;
@@ -205,32 +599,37 @@ impl<Chan: Channel, const CH: usize> Frame<Chan, CH> {
             // Front Center - Front Right Speakers (30° slice)
             x if x < 30.0 / 360.0 => {
                 let x = (360.0 / 30.0) * x * FRAC_PI_2;
-                self.0[FRONT] += chan * x.cos().into();
-                self.0[FRONT_R] += chan * x.sin().into();
+                let (near, far) = law.gains(x);
+                self.0[FRONT] += chan * near.into();
+                self.0[FRONT_R] += chan * far.into();
             }
             // Front Right - Back Right Speakers (80° slice)
             x if x < 110.0 / 360.0 => {
                 let x = (360.0 / 80.0) * (x - 30.0 / 360.0) * FRAC_PI_2;
-                self.0[FRONT_R] += chan * x.cos().into();
-                self.0[SURROUND_R] += chan * x.sin().into();
+                let (near, far) = law.gains(x);
+                self.0[FRONT_R] += chan * near.into();
+                self.0[SURROUND_R] += chan * far.into();
             }
             // Back Right - Back Left Speakers (140° slice)
             x if x < 250.0 / 360.0 => {
                 let x = (360.0 / 140.0) * (x - 110.0 / 360.0) * FRAC_PI_2;
-                self.0[SURROUND_R] += chan * x.cos().into();
-                self.0[SURROUND_L] += chan * x.sin().into();
+                let (near, far) = law.gains(x);
+                self.0[SURROUND_R] += chan * near.into();
+                self.0[SURROUND_L] += chan * far.into();
             }
             // Back Left - Front Left Speakers (80° slice)
             x if x < 330.0 / 360.0 => {
                 let x = (360.0 / 80.0) * (x - 250.0 / 360.0) * FRAC_PI_2;
-                self.0[SURROUND_L] += chan * x.cos().into();
-                self.0[FRONT_L] += chan * x.sin().into();
+                let (near, far) = law.gains(x);
+                self.0[SURROUND_L] += chan * near.into();
+                self.0[FRONT_L] += chan * far.into();
             }
             // Front Left - Center Speakers (30° slice)
             x => {
                 let x = (360.0 / 30.0) * (x - 330.0 / 360.0) * FRAC_PI_2;
-                self.0[FRONT_L] += chan * x.cos().into();
-                self.0[FRONT] += chan * x.sin().into();
+                let (near, far) = law.gains(x);
+                self.0[FRONT_L] += chan * near.into();
+                self.0[FRONT] += chan * far.into();
             }
         }
 
@@ -238,7 +637,7 @@ impl<Chan: Channel, const CH: usize> Frame<Chan, CH> {
     }
 
     #[inline(always)]
-    fn pan_6(mut self, chan: Chan, x: f32) -> Self {
+    fn pan_6(mut self, chan: Chan, x: f32, law: PanLaw) -> Self {
         const FRONT_L: usize = 0;
         const FRONT_R: usize = 1;
         const FRONT: usize = 2;
@@ -250,32 +649,37 @@ impl<Chan: Channel, const CH: usize> Frame<Chan, CH> {
             // Front Center - Front Right Speakers (30° slice)
             x if x < 30.0 / 360.0 => {
                 let x = (360.0 / 30.0) * x * FRAC_PI_2;
-                self.0[FRONT] += chan * x.cos().into();
-                self.0[FRONT_R] += chan * x.sin().into();
+                let (near, far) = law.gains(x);
+                self.0[FRONT] += chan * near.into();
+                self.0[FRONT_R] += chan * far.into();
             }
             // Front Right - Back Right Speakers (80° slice)
             x if x < 110.0 / 360.0 => {
                 let x = (360.0 / 80.0) * (x - 30.0 / 360.0) * FRAC_PI_2;
-                self.0[FRONT_R] += chan * x.cos().into();
-                self.0[SURROUND_R] += chan * x.sin().into();
+                let (near, far) = law.gains(x);
+                self.0[FRONT_R] += chan * near.into();
+                self.0[SURROUND_R] += chan * far.into();
             }
             // Back Right - Back Left Speakers (140° slice)
             x if x < 250.0 / 360.0 => {
                 let x = (360.0 / 140.0) * (x - 110.0 / 360.0) * FRAC_PI_2;
-                self.0[SURROUND_R] += chan * x.cos().into();
-                self.0[SURROUND_L] += chan * x.sin().into();
+                let (near, far) = law.gains(x);
+                self.0[SURROUND_R] += chan * near.into();
+                self.0[SURROUND_L] += chan * far.into();
             }
             // Back Left - Front Left Speakers (80° slice)
             x if x < 330.0 / 360.0 => {
                 let x = (360.0 / 80.0) * (x - 250.0 / 360.0) * FRAC_PI_2;
-                self.0[SURROUND_L] += chan * x.cos().into();
-                self.0[FRONT_L] += chan * x.sin().into();
+                let (near, far) = law.gains(x);
+                self.0[SURROUND_L] += chan * near.into();
+                self.0[FRONT_L] += chan * far.into();
             }
             // Front Left - Center Speakers (30° slice)
             x => {
                 let x = (360.0 / 30.0) * (x - 330.0 / 360.0) * FRAC_PI_2;
-                self.0[FRONT_L] += chan * x.cos().into();
-                self.0[FRONT] += chan * x.sin().into();
+                let (near, far) = law.gains(x);
+                self.0[FRONT_L] += chan * near.into();
+                self.0[FRONT] += chan * far.into();
             }
         }
 
@@ -283,7 +687,7 @@ impl<Chan: Channel, const CH: usize> Frame<Chan, CH> {
     }
 
     #[inline(always)]
-    fn pan_7(mut self, chan: Chan, x: f32) -> Self {
+    fn pan_7(mut self, chan: Chan, x: f32, law: PanLaw) -> Self {
         const FRONT_L: usize = 0;
         const FRONT_R: usize = 1;
         const FRONT: usize = 2;
@@ -296,38 +700,44 @@ impl<Chan: Channel, const CH: usize> Frame<Chan, CH> {
             // Front Center - Front Right Speakers (30° slice)
             x if x < 30.0 / 360.0 => {
                 let x = (360.0 / 30.0) * x * FRAC_PI_2;
-                self.0[FRONT] += chan * x.cos().into();
-                self.0[FRONT_R] += chan * x.sin().into();
+                let (near, far) = law.gains(x);
+                self.0[FRONT] += chan * near.into();
+                self.0[FRONT_R] += chan * far.into();
             }
             // Front Right - Side Right Speakers (60° slice)
             x if x < 90.0 / 360.0 => {
                 let x = (360.0 / 60.0) * (x - 30.0 / 360.0) * FRAC_PI_2;
-                self.0[FRONT_R] += chan * x.cos().into();
-                self.0[RIGHT] += chan * x.sin().into();
+                let (near, far) = law.gains(x);
+                self.0[FRONT_R] += chan * near.into();
+                self.0[RIGHT] += chan * far.into();
             }
             // Side Right - Back Speakers (90° slice)
             x if x < 180.0 / 360.0 => {
                 let x = (360.0 / 90.0) * (x - 90.0 / 360.0) * FRAC_PI_2;
-                self.0[RIGHT] += chan * x.cos().into();
-                self.0[BACK] += chan * x.sin().into();
+                let (near, far) = law.gains(x);
+                self.0[RIGHT] += chan * near.into();
+                self.0[BACK] += chan * far.into();
             }
             // Back - Side Left Speakers (90° slice)
             x if x < 270.0 / 360.0 => {
                 let x = (360.0 / 90.0) * (x - 180.0 / 360.0) * FRAC_PI_2;
-                self.0[BACK] += chan * x.cos().into();
-                self.0[LEFT] += chan * x.sin().into();
+                let (near, far) = law.gains(x);
+                self.0[BACK] += chan * near.into();
+                self.0[LEFT] += chan * far.into();
             }
             // Side Left - Front Left Speakers (60° slice)
             x if x < 330.0 / 360.0 => {
                 let x = (360.0 / 60.0) * (x - 270.0 / 360.0) * FRAC_PI_2;
-                self.0[LEFT] += chan * x.cos().into();
-                self.0[FRONT_L] += chan * x.sin().into();
+                let (near, far) = law.gains(x);
+                self.0[LEFT] += chan * near.into();
+                self.0[FRONT_L] += chan * far.into();
             }
             // Front Left - Center Speakers (30° slice)
             x => {
                 let x = (360.0 / 30.0) * (x - 330.0 / 360.0) * FRAC_PI_2;
-                self.0[FRONT_L] += chan * x.cos().into();
-                self.0[FRONT] += chan * x.sin().into();
+                let (near, far) = law.gains(x);
+                self.0[FRONT_L] += chan * near.into();
+                self.0[FRONT] += chan * far.into();
             }
         }
 
@@ -335,7 +745,7 @@ impl<Chan: Channel, const CH: usize> Frame<Chan, CH> {
     }
 
     #[inline(always)]
-    fn pan_8(mut self, chan: Chan, x: f32) -> Self {
+    fn pan_8(mut self, chan: Chan, x: f32, law: PanLaw) -> Self {
         const FRONT_L: usize = 0;
         const FRONT_R: usize = 1;
         const FRONT: usize = 2;
@@ -349,44 +759,51 @@ impl<Chan: Channel, const CH: usize> Frame<Chan, CH> {
             // Front Center - Front Right Speakers (30° slice)
             x if x < 30.0 / 360.0 => {
                 let x = (360.0 / 30.0) * x * FRAC_PI_2;
-                self.0[FRONT] += chan * x.cos().into();
-                self.0[FRONT_R] += chan * x.sin().into();
+                let (near, far) = law.gains(x);
+                self.0[FRONT] += chan * near.into();
+                self.0[FRONT_R] += chan * far.into();
             }
             // Front Right - Side Right Speakers (60° slice)
             x if x < 90.0 / 360.0 => {
                 let x = (360.0 / 60.0) * (x - 30.0 / 360.0) * FRAC_PI_2;
-                self.0[FRONT_R] += chan * x.cos().into();
-                self.0[RIGHT] += chan * x.sin().into();
+                let (near, far) = law.gains(x);
+                self.0[FRONT_R] += chan * near.into();
+                self.0[RIGHT] += chan * far.into();
             }
             // Side Right - Back Right Speakers (60° slice)
             x if x < 150.0 / 360.0 => {
                 let x = (360.0 / 60.0) * (x - 90.0 / 360.0) * FRAC_PI_2;
-                self.0[RIGHT] += chan * x.cos().into();
-                self.0[BACK_R] += chan * x.sin().into();
+                let (near, far) = law.gains(x);
+                self.0[RIGHT] += chan * near.into();
+                self.0[BACK_R] += chan * far.into();
             }
             // Back Right - Back Left Speakers (60° slice)
             x if x < 210.0 / 360.0 => {
                 let x = (360.0 / 60.0) * (x - 150.0 / 360.0) * FRAC_PI_2;
-                self.0[BACK_R] += chan * x.cos().into();
-                self.0[BACK_L] += chan * x.sin().into();
+                let (near, far) = law.gains(x);
+                self.0[BACK_R] += chan * near.into();
+                self.0[BACK_L] += chan * far.into();
             }
             // Back Left - Side Left Speakers (60° slice)
             x if x < 270.0 / 360.0 => {
                 let x = (360.0 / 60.0) * (x - 210.0 / 360.0) * FRAC_PI_2;
-                self.0[BACK_L] += chan * x.cos().into();
-                self.0[LEFT] += chan * x.sin().into();
+                let (near, far) = law.gains(x);
+                self.0[BACK_L] += chan * near.into();
+                self.0[LEFT] += chan * far.into();
             }
             // Side Left - Front Left Speakers (60° slice)
             x if x < 330.0 / 360.0 => {
                 let x = (360.0 / 60.0) * (x - 270.0 / 360.0) * FRAC_PI_2;
-                self.0[LEFT] += chan * x.cos().into();
-                self.0[FRONT_L] += chan * x.sin().into();
+                let (near, far) = law.gains(x);
+                self.0[LEFT] += chan * near.into();
+                self.0[FRONT_L] += chan * far.into();
             }
             // Front Left - Center Speakers (30° slice)
             x => {
                 let x = (360.0 / 30.0) * (x - 330.0 / 360.0) * FRAC_PI_2;
-                self.0[FRONT_L] += chan * x.cos().into();
-                self.0[FRONT] += chan * x.sin().into();
+                let (near, far) = law.gains(x);
+                self.0[FRONT_L] += chan * near.into();
+                self.0[FRONT] += chan * far.into();
             }
         }
 
@@ -618,6 +1035,32 @@ impl<Chan: Channel, const CH: usize> Frame<Chan, CH> {
             frame
         }
     }
+
+    #[inline(always)]
+    fn to_12<C: Channel + From<Chan>, const N: usize>(self) -> Frame<C, N> {
+        const TOP_FRONT_L: usize = 8;
+        const TOP_FRONT_R: usize = 9;
+        const TOP_BACK_L: usize = 10;
+        const TOP_BACK_R: usize = 11;
+
+        if N == 12 {
+            let mut frame = Frame::<C, N>::default();
+            for (out, chan) in frame.0.iter_mut().zip(self.0.iter()) {
+                *out = (*chan).into();
+            }
+            return frame;
+        }
+
+        // Fold the height channels into the 7.1 bed at -3 dB.
+        let mut bed = Frame::<Chan, 8>::default();
+        bed.0.copy_from_slice(&self.0[..8]);
+        let height = FRAC_1_SQRT_2;
+        bed.pan(self.0[TOP_FRONT_L] * height.into(), -45.0 / 360.0)
+            .pan(self.0[TOP_FRONT_R] * height.into(), 45.0 / 360.0)
+            .pan(self.0[TOP_BACK_L] * height.into(), -135.0 / 360.0)
+            .pan(self.0[TOP_BACK_R] * height.into(), 135.0 / 360.0)
+            .to()
+    }
 }
 
 impl<Chan: Channel> Frame<Chan, 1> {
@@ -723,6 +1166,89 @@ impl<Chan: Channel> Frame<Chan, 8> {
     }
 }
 
+impl<Chan: Channel> Frame<Chan, 12> {
+    /// Create a new surround 7.1.4 interleaved audio frame from channel(s).
+    #[inline(always)]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        left: Chan,
+        right: Chan,
+        center: Chan,
+        lfe: Chan,
+        back_left: Chan,
+        back_right: Chan,
+        side_left: Chan,
+        side_right: Chan,
+        top_front_left: Chan,
+        top_front_right: Chan,
+        top_back_left: Chan,
+        top_back_right: Chan,
+    ) -> Self {
+        Self([
+            left,
+            right,
+            center,
+            lfe,
+            back_left,
+            back_right,
+            side_left,
+            side_right,
+            top_front_left,
+            top_front_right,
+            top_back_left,
+            top_back_right,
+        ])
+    }
+
+    /// Mix a panned channel into this audio frame at an elevation.
+    ///
+    /// `angle` is the same as for [`pan()`](Frame::pan), and `elevation` is
+    /// 0.0 at ear level, or 0.125 (45°) at the height of the top speakers.
+    /// Elevations in between crossfade from the 7.1 bed into the top speakers
+    /// with constant power, and higher elevations only use the top speakers.
+    #[inline(always)]
+    pub fn pan_elevated<C: Channel + Into<Chan>>(
+        self,
+        channel: C,
+        angle: f32,
+        elevation: f32,
+    ) -> Self {
+        let chan: Chan = channel.into();
+        let x = (elevation * 8.0).clamp(0.0, 1.0) * FRAC_PI_2;
+        self.pan(chan * x.cos().into(), angle)
+            .pan_top(chan * x.sin().into(), angle.rem_euclid(1.0))
+    }
+
+    #[inline(always)]
+    fn pan_top(mut self, chan: Chan, x: f32) -> Self {
+        const TOP_FRONT_L: usize = 8;
+        const TOP_FRONT_R: usize = 9;
+        const TOP_BACK_L: usize = 10;
+        const TOP_BACK_R: usize = 11;
+
+        // Rotate so the top front left speaker is at 0.
+        let (near, far, x) = match (x + 45.0 / 360.0).fract() {
+            // Top Front Left - Top Front Right Speakers (90° slice)
+            x if x < 90.0 / 360.0 => (TOP_FRONT_L, TOP_FRONT_R, x),
+            // Top Front Right - Top Back Right Speakers (90° slice)
+            x if x < 180.0 / 360.0 => {
+                (TOP_FRONT_R, TOP_BACK_R, x - 90.0 / 360.0)
+            }
+            // Top Back Right - Top Back Left Speakers (90° slice)
+            x if x < 270.0 / 360.0 => {
+                (TOP_BACK_R, TOP_BACK_L, x - 180.0 / 360.0)
+            }
+            // Top Back Left - Top Front Left Speakers (90° slice)
+            x => (TOP_BACK_L, TOP_FRONT_L, x - 270.0 / 360.0),
+        };
+        let (near_gain, far_gain) =
+            PanLaw::ConstantPower.gains((360.0 / 90.0) * x * FRAC_PI_2);
+        self.0[near] += chan * near_gain.into();
+        self.0[far] += chan * far_gain.into();
+        self
+    }
+}
+
 impl<Chan: Channel, const CH: usize> From<f32> for Frame<Chan, CH> {
     fn from(rhs: f32) -> Self {
         Frame([Chan::from(rhs); CH])
@@ -765,6 +1291,18 @@ impl<Chan: Channel, const CH: usize> Mul for Frame<Chan, CH> {
     }
 }
 
+impl<Chan: Channel, const CH: usize> Mul<Chan> for Frame<Chan, CH> {
+    type Output = Self;
+
+    #[inline(always)]
+    fn mul(mut self, other: Chan) -> Self {
+        for chan in self.0.iter_mut() {
+            *chan *= other;
+        }
+        self
+    }
+}
+
 impl<Chan: Channel, const CH: usize> Neg for Frame<Chan, CH> {
     type Output = Self;
 
@@ -776,3 +1314,286 @@ impl<Chan: Channel, const CH: usize> Neg for Frame<Chan, CH> {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chan::{Ch16, Ch32};
+
+    #[test]
+    fn pan_law() {
+        let center = |law| {
+            let frame = Frame::<Ch32, 2>::default();
+            *frame.pan_with(Ch32::new(1.0), 0.0, law).channels()
+        };
+        let [l, r] = center(PanLaw::Linear);
+        assert!((l.to_f32() - 0.5).abs() < 1e-6);
+        assert!((r.to_f32() - 0.5).abs() < 1e-6);
+        let [l, r] = center(PanLaw::ConstantPower);
+        assert!((l.to_f32() - FRAC_1_SQRT_2).abs() < 1e-6);
+        assert!((r.to_f32() - FRAC_1_SQRT_2).abs() < 1e-6);
+        let [l, r] = center(PanLaw::Balanced);
+        assert!((l.to_f32() - 0.59460356).abs() < 1e-6);
+        assert!((r.to_f32() - 0.59460356).abs() < 1e-6);
+
+        // Hard left (phase inverted, past the back) and hard right.
+        for law in [PanLaw::Linear, PanLaw::ConstantPower, PanLaw::Balanced] {
+            let frame = Frame::<Ch32, 2>::default();
+            let [l, r] = *frame.pan_with(Ch32::new(1.0), 0.75, law).channels();
+            assert!((l.to_f32() + 1.0).abs() < 1e-6);
+            assert!(r.to_f32().abs() < 1e-6);
+            let [l, r] = *frame.pan_with(Ch32::new(1.0), 0.25, law).channels();
+            assert!(l.to_f32().abs() < 1e-6);
+            assert!((r.to_f32() - 1.0).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn abs_signum() {
+        let frame = Frame::<Ch32, 3>::new(
+            Ch32::new(0.25),
+            Ch32::new(0.0),
+            Ch32::new(1.0),
+        );
+        assert_eq!((-frame).abs(), frame);
+        assert_eq!(frame.abs(), frame);
+        let frame = Frame::<Ch16, 2>::new(Ch16::new(1000), Ch16::new(i16::MAX));
+        assert_eq!((-frame).abs(), frame);
+
+        let frame = Frame::<Ch32, 3>::new(
+            Ch32::new(-0.25),
+            Ch32::new(0.0),
+            Ch32::new(0.5),
+        );
+        assert_eq!(
+            frame.signum(),
+            Frame::<Ch32, 3>::new(
+                Ch32::new(-1.0),
+                Ch32::new(0.0),
+                Ch32::new(1.0)
+            )
+        );
+        let frame = Frame::<Ch16, 2>::new(Ch16::new(-3), Ch16::new(0));
+        assert_eq!(frame.signum(), Frame::<Ch16, 2>::new(Ch16::MIN, Ch16::MID));
+    }
+
+    #[test]
+    fn max_min_channel() {
+        let frame = Frame::<Ch32, 3>::new(
+            Ch32::new(0.25),
+            Ch32::new(-0.75),
+            Ch32::new(0.125),
+        );
+        assert_eq!(frame.max_channel(), (1, Ch32::new(-0.75)));
+        assert_eq!(frame.min_channel(), (2, Ch32::new(0.125)));
+        assert_eq!(frame.peak_channel_abs(), 0.75);
+
+        let frame = Frame::<Ch32, 2>::new(Ch32::new(0.5), Ch32::new(0.5));
+        assert_eq!(frame.max_channel().0, 0);
+        assert_eq!(frame.min_channel().0, 0);
+    }
+
+    #[test]
+    fn blend() {
+        let a = Frame::<Ch32, 3>::new(
+            Ch32::new(0.5),
+            Ch32::new(-0.5),
+            Ch32::new(0.75),
+        );
+        let b = Frame::<Ch32, 3>::new(
+            Ch32::new(0.25),
+            Ch32::new(-0.5),
+            Ch32::new(-0.25),
+        );
+        let blend = |op| {
+            let frame = a.blend(b, op);
+            frame.channels().map(|chan| chan.to_f32())
+        };
+        assert_eq!(blend(Blend::Add), [0.75, -1.0, 0.5]);
+        assert_eq!(blend(Blend::Multiply), [0.125, 0.25, -0.1875]);
+        assert_eq!(blend(Blend::Min), [0.25, -0.5, -0.25]);
+        assert_eq!(blend(Blend::Max), [0.5, -0.5, 0.75]);
+        assert_eq!(blend(Blend::Overwrite), [0.25, -0.5, -0.25]);
+        assert_eq!(blend(Blend::Screen), [0.625, -0.75, 0.5]);
+    }
+
+    #[test]
+    fn gate() {
+        let frame = Frame::<Ch32, 3>::new(
+            Ch32::new(0.05),
+            Ch32::new(-0.5),
+            Ch32::new(-0.05),
+        );
+        assert_eq!(
+            frame.gate(0.1),
+            Frame::<Ch32, 3>::new(
+                Ch32::new(0.0),
+                Ch32::new(-0.5),
+                Ch32::new(0.0)
+            )
+        );
+    }
+
+    #[test]
+    fn mul_channel() {
+        let frame = Frame::<Ch32, 2>::new(Ch32::new(1.0), Ch32::new(-0.5));
+        assert_eq!(
+            frame * Ch32::new(0.5),
+            Frame::<Ch32, 2>::new(Ch32::new(0.5), Ch32::new(-0.25))
+        );
+        let frame = Frame::<Ch16, 1>::new(Ch16::MAX);
+        assert_eq!(frame * Ch16::MID, Frame::<Ch16, 1>::new(Ch16::MID));
+    }
+
+    #[test]
+    fn surround_7_1_4() {
+        use crate::pos::{FrontL, Lfe, Right, TopBackR, TopFrontL};
+
+        let ch = |x| Ch32::new(x);
+        let mut frame = Frame::<Ch32, 12>::new(
+            ch(0.1),
+            ch(0.0),
+            ch(0.0),
+            ch(0.2),
+            ch(0.0),
+            ch(0.0),
+            ch(0.0),
+            ch(0.3),
+            ch(0.4),
+            ch(0.0),
+            ch(0.0),
+            ch(0.5),
+        );
+        assert_eq!(frame[FrontL], ch(0.1));
+        assert_eq!(frame[Lfe], ch(0.2));
+        assert_eq!(frame[Right], ch(0.3));
+        assert_eq!(frame[TopFrontL], ch(0.4));
+        assert_eq!(frame[TopBackR], ch(0.5));
+        frame[TopFrontL] = ch(0.6);
+        assert_eq!(frame.channels()[8], ch(0.6));
+
+        // Height channels fold into the nearest mains.
+        let frame = Frame::<Ch32, 12>::default().pan_elevated(
+            ch(1.0),
+            -45.0 / 360.0,
+            0.25,
+        );
+        assert!((frame[TopFrontL].to_f32() - 1.0).abs() < 1e-6);
+        let bed = frame.to::<Ch32, 8>();
+        let [fl, fr, c, lfe, bl, br, l, r] = bed.channels().map(Ch32::to_f32);
+        assert!(fl > FRAC_1_SQRT_2 * 0.5 && l > 0.0);
+        assert!(fl + l > FRAC_1_SQRT_2);
+        assert!([fr, c, lfe, bl, br, r].iter().all(|x| x.abs() < 1e-6));
+
+        // The 7.1 bed passes through unchanged.
+        let frame = Frame::<Ch32, 12>::default().pan(ch(1.0), 0.25);
+        assert_eq!(frame.to::<Ch32, 8>().channels()[..], frame.channels()[..8]);
+        assert_eq!(frame.to::<Ch32, 12>(), frame);
+
+        // Elevation crossfades between the bed and the top speakers.
+        let frame =
+            Frame::<Ch32, 12>::default().pan_elevated(ch(1.0), 0.0, 0.0);
+        assert!(frame.channels()[8..].iter().all(|x| x.to_f32() == 0.0));
+        let frame =
+            Frame::<Ch32, 12>::default().pan_elevated(ch(1.0), 0.0, 0.25);
+        assert!(frame.channels()[..8]
+            .iter()
+            .all(|x| x.to_f32().abs() < 1e-6));
+        let [tfl, tfr, tbl, tbr] =
+            [8, 9, 10, 11].map(|i| frame.channels()[i].to_f32());
+        assert!((tfl - FRAC_1_SQRT_2).abs() < 1e-6);
+        assert!((tfr - FRAC_1_SQRT_2).abs() < 1e-6);
+        assert!(tbl.abs() < 1e-6 && tbr.abs() < 1e-6);
+    }
+
+    #[test]
+    fn flush_subnormals() {
+        let mut frame = Frame::<Ch32, 3>::new(
+            Ch32::new(1e-40),
+            Ch32::new(1e-30),
+            Ch32::new(-1e-40),
+        );
+        frame.flush_subnormals();
+        assert_eq!(
+            frame,
+            Frame::<Ch32, 3>::new(Ch32::new(0.0), Ch32::new(1e-30), Ch32::MID)
+        );
+        let mut frame = Frame::<Ch16, 1>::new(Ch16::new(1));
+        frame.flush_subnormals();
+        assert_eq!(frame, Frame::<Ch16, 1>::new(Ch16::new(1)));
+    }
+
+    #[test]
+    fn lerp_spatial() {
+        let ch = |x| Ch32::new(x);
+        let panned = |angle| Frame::<Ch32, 8>::default().pan(ch(1.0), angle);
+        let close = |a: Frame<Ch32, 8>, b: Frame<Ch32, 8>| {
+            a.channels()
+                .iter()
+                .zip(b.channels())
+                .all(|(a, b)| (a.to_f32() - b.to_f32()).abs() < 1e-4)
+        };
+
+        // Sources on opposite sides move through the front at full level.
+        for (t, angle) in [(0.0, -0.2), (0.25, -0.1), (0.5, 0.0), (1.0, 0.2)] {
+            let mut frame = panned(-0.2);
+            frame.lerp_spatial(panned(0.2), t);
+            assert!(close(frame, panned(angle)), "{} {:?}", t, frame);
+            let power = frame.channels().iter().map(|x| x.to_f32().powi(2));
+            assert!((power.sum::<f32>() - 1.0).abs() < 1e-4);
+        }
+
+        // Shortest way round, through the back.
+        let mut frame = panned(0.4);
+        frame.lerp_spatial(panned(-0.3), 0.5);
+        assert!(close(frame, panned(0.55)));
+
+        // The LFE is interpolated linearly.
+        let mut a = panned(0.1);
+        a.channels_mut()[3] = ch(0.2);
+        let mut b = panned(0.1);
+        b.channels_mut()[3] = ch(0.6);
+        a.lerp_spatial(b, 0.5);
+        let mut expected = panned(0.1);
+        expected.channels_mut()[3] = ch(0.4);
+        assert!(close(a, expected));
+
+        // Stereo is interpolated linearly.
+        let mut frame = Frame::<Ch32, 2>::default();
+        let left = Frame::<Ch32, 2>::new(ch(1.0), ch(0.0));
+        frame.lerp_spatial(left, 0.5);
+        let [l, r] = frame.channels().map(Ch32::to_f32);
+        assert!((l - 0.5).abs() < 1e-6 && r.abs() < 1e-6);
+    }
+
+    #[test]
+    fn to_dyn() {
+        let mono = Frame::<Ch32, 1>::new(Ch32::new(0.5));
+        let frame = mono.to_dyn(2);
+        assert_eq!(frame.channels(), mono.to::<Ch32, 2>().channels());
+
+        let surround = Frame::<Ch32, 6>::new(
+            Ch32::new(0.1),
+            Ch32::new(0.2),
+            Ch32::new(0.3),
+            Ch32::new(0.4),
+            Ch32::new(0.5),
+            Ch32::new(0.6),
+        );
+        let frame = surround.to_dyn(6);
+        assert_eq!(frame.channels(), surround.to::<Ch32, 6>().channels());
+        let frame = surround.to_dyn(1);
+        assert_eq!(frame.channels(), surround.to::<Ch32, 1>().channels());
+
+        let frame = DynFrame::try_from(surround).unwrap();
+        assert_eq!(frame.channels(), surround.channels());
+        let frame = surround.to_dyn(12);
+        assert_eq!(frame.channels(), surround.to::<Ch32, 12>().channels());
+        let frame = DynFrame::try_from(surround.to::<Ch32, 12>()).unwrap();
+        assert_eq!(frame.channels().len(), 12);
+        assert_eq!(
+            DynFrame::try_from(Frame::<Ch32, 16>::default()),
+            Err(AudioError::UnsupportedLayout { channels: 16 })
+        );
+    }
+}
@@ -0,0 +1,111 @@
+// Copyright © 2020-2022 The Fon Contributors.
+//
+// Licensed under any of:
+// - Apache License, Version 2.0 (https://www.apache.org/licenses/LICENSE-2.0)
+// - MIT License (https://mit-license.org/)
+// - Boost Software License, Version 1.0 (https://www.boost.org/LICENSE_1_0.txt)
+// At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
+// LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
+
+//! Low frequency oscillators for modulation effects.
+
+use_libm!();
+
+use core::f32::consts::PI;
+use core::num::NonZeroU32;
+
+/// Waveform of an [`Lfo`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LfoShape {
+    /// Sine wave.
+    Sine,
+    /// Triangle wave, rising at the start of each cycle like the sine wave.
+    Triangle,
+    /// Square wave, high for the first half of each cycle.
+    Square,
+}
+
+/// Low frequency oscillator, generating one modulation value per frame.
+///
+/// The rate is set in hertz, independent of the sample rate of the audio
+/// being modulated, so the sample rate is passed in with each frame.
+#[derive(Copy, Clone, Debug)]
+pub struct Lfo {
+    shape: LfoShape,
+    rate_hz: f32,
+    depth: f32,
+    /// Position in the current cycle, from 0 to 1.
+    phase: f32,
+}
+
+impl Lfo {
+    /// Create a new LFO, starting at the beginning of its cycle.
+    pub fn new(shape: LfoShape, rate_hz: f32, depth: f32) -> Self {
+        Self {
+            shape,
+            rate_hz,
+            depth,
+            phase: 0.0,
+        }
+    }
+
+    /// Create a new sine wave LFO.
+    pub fn sine(rate_hz: f32, depth: f32) -> Self {
+        Self::new(LfoShape::Sine, rate_hz, depth)
+    }
+
+    /// Create a new triangle wave LFO.
+    pub fn triangle(rate_hz: f32, depth: f32) -> Self {
+        Self::new(LfoShape::Triangle, rate_hz, depth)
+    }
+
+    /// Create a new square wave LFO.
+    pub fn square(rate_hz: f32, depth: f32) -> Self {
+        Self::new(LfoShape::Square, rate_hz, depth)
+    }
+
+    /// Get the modulation value for the next frame (from `-depth` to
+    /// `depth`), advancing by one frame at `sample_rate`.
+    pub fn next(&mut self, sample_rate: NonZeroU32) -> f32 {
+        let wave = match self.shape {
+            LfoShape::Sine => (2.0 * PI * self.phase).sin(),
+            LfoShape::Triangle => {
+                4.0 * ((self.phase + 0.75).fract() - 0.5).abs() - 1.0
+            }
+            LfoShape::Square => {
+                if self.phase < 0.5 {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+        };
+        self.phase = (self.phase + self.rate_hz / sample_rate.get() as f32)
+            .rem_euclid(1.0);
+        wave * self.depth
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shapes() {
+        let hz = NonZeroU32::new(8).unwrap();
+        let values = |mut lfo: Lfo| [(); 8].map(|_| lfo.next(hz));
+        assert_eq!(
+            values(Lfo::triangle(1.0, 0.5)),
+            [0.0, 0.25, 0.5, 0.25, 0.0, -0.25, -0.5, -0.25]
+        );
+        assert_eq!(
+            values(Lfo::square(2.0, 1.0)),
+            [1.0, 1.0, -1.0, -1.0, 1.0, 1.0, -1.0, -1.0]
+        );
+        let sine = values(Lfo::sine(1.0, 1.0));
+        for (i, value) in sine.iter().enumerate() {
+            let expected = (i as f32 * PI / 4.0).sin();
+            assert!((value - expected).abs() < 0.0001);
+        }
+    }
+}
@@ -10,7 +10,7 @@
 //! Rust audio types and conversions.
 //!
 //! An [audio buffer] can be cheaply converted to and from raw samples (i16, u8,
-//! f32, and f64) buffers, enabling interoperability with other crates.
+//! i32, f32, and f64) buffers, enabling interoperability with other crates.
 //!
 //! Many audio formats are supported:
 //!  - Any integer sample rate (32 bits needed to support at least 96_000 Hz)
@@ -18,9 +18,10 @@
 //!    fake unsigned by XOR'ing the top bit)
 //!    - [16-bit Signed Integer PCM] (Listening/publishing standard)
 //!    - [24-bit Signed Integer PCM] (Older recording/processing standard)
+//!    - [32-bit Signed Integer PCM] (Professional audio interfaces)
 //!    - [32-bit Float PCM] (Newer recording/processing standard)
 //!    - [64-bit Float PCM] (Ultra high-quality audio standard)
-//!  - Up to 8 channels (following FLAC/SMPTE/ITU-R recommendations):
+//!  - Up to 12 channels (following FLAC/SMPTE/ITU-R recommendations):
 //!    - 1 Channel: Mono ([Mono])
 //!    - 2 Channels: Stereo ([Left], [Right])
 //!    - 3 Channels: Surround 3.0 ([Left], [Right], [Center])
@@ -33,6 +34,9 @@
 //!      [Left], [Right])
 //!    - 8 Channels: Surround 7.1 ([FrontL], [FrontR], [Front], [Lfe], [BackL],
 //!      [BackR], [Left], [Right])
+//!    - 12 Channels: Surround 7.1.4 ([FrontL], [FrontR], [Front], [Lfe],
+//!      [BackL], [BackR], [Left], [Right], [TopFrontL], [TopFrontR],
+//!      [TopBackL], [TopBackR])
 //!
 //! # Getting Started
 //! To understand some of the concepts used in this library,
@@ -60,6 +64,7 @@
 //! [audio buffer]: crate::Audio
 //! [16-bit Signed Integer PCM]: crate::chan::Ch16
 //! [24-bit Signed Integer PCM]: crate::chan::Ch24
+//! [32-bit Signed Integer PCM]: crate::chan::Ch32i
 //! [32-bit Float PCM]: crate::chan::Ch32
 //! [64-bit Float PCM]: crate::chan::Ch64
 //! [operations]: crate::ops
@@ -77,6 +82,10 @@
 //! [Back]: crate::pos::Back
 //! [BackL]: crate::pos::BackL
 //! [BackR]: crate::pos::BackR
+//! [TopFrontL]: crate::pos::TopFrontL
+//! [TopFrontR]: crate::pos::TopFrontR
+//! [TopBackL]: crate::pos::TopBackL
+//! [TopBackR]: crate::pos::TopBackR
 
 #![no_std]
 #![doc(
@@ -103,18 +112,48 @@
 
 extern crate alloc;
 
+/// Import the `Libm` float methods, unless std is linked (by tests, or the
+/// `rayon` or `dasp` features) and already provides them.
+macro_rules! use_libm {
+    () => {
+        #[cfg(not(any(test, feature = "rayon", feature = "dasp")))]
+        use crate::math::Libm;
+    };
+}
+
 mod audio;
+mod builder;
+#[cfg(feature = "dasp")]
+mod dasp;
+mod error;
+mod filter;
 mod frame;
+mod lfo;
+#[cfg_attr(any(feature = "rayon", feature = "dasp"), allow(dead_code))]
 mod math;
+mod mix;
 mod private;
 mod sink;
+mod stats;
 mod stream;
 
 pub mod chan;
 
 pub mod pos;
 
-pub use audio::{Audio, AudioSink};
-pub use frame::Frame;
-pub use sink::{Sink, SinkTo};
-pub use stream::Stream;
+pub use audio::{
+    frames_to_ms, guess_channels, infer_sample_rate, ms_to_frames, Audio,
+    AudioSink, Compatibility, Converter, Expander, MonoBuffer, RateMode, Side,
+};
+pub use builder::{AudioBuilder, ChannelLayout};
+pub use error::AudioError;
+pub use filter::{DcBlocker, FirFilter};
+pub use frame::{Blend, DynFrame, Frame, PanLaw};
+pub use lfo::{Lfo, LfoShape};
+pub use mix::MixBuffer;
+pub use sink::{LimiterSink, Sink, SinkTo};
+pub use stats::{Meter, Stats, StatsAccumulator};
+pub use stream::{
+    resample_f32_interleaved, resample_iter, ResampleWriter, ResamplerFilter,
+    Stream, Transcoder, Window,
+};
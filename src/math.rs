@@ -13,11 +13,18 @@ use core::ops::Rem;
 /// implemented with the libm crate as dependency of core in the future.
 pub(crate) trait Libm: Rem<Output = Self> + Sized {
     fn sin(self) -> Self;
+    fn asin(self) -> Self;
     fn cos(self) -> Self;
+    fn tan(self) -> Self;
     fn floor(self) -> Self;
     fn ceil(self) -> Self;
+    fn round(self) -> Self;
     fn abs(self) -> Self;
+    fn sqrt(self) -> Self;
+    fn exp(self) -> Self;
     fn trunc(self) -> Self;
+    fn log10(self) -> Self;
+    fn ln(self) -> Self;
     fn powi(self, n: i32) -> Self;
     fn rem_euclid(self, rhs: Self) -> Self;
     fn fract(self) -> Self;
@@ -29,11 +36,21 @@ impl Libm for f32 {
         libm::sinf(self)
     }
 
+    #[inline(always)]
+    fn asin(self) -> Self {
+        libm::asinf(self)
+    }
+
     #[inline(always)]
     fn cos(self) -> Self {
         libm::cosf(self)
     }
 
+    #[inline(always)]
+    fn tan(self) -> Self {
+        libm::tanf(self)
+    }
+
     #[inline(always)]
     fn floor(self) -> Self {
         libm::floorf(self)
@@ -44,16 +61,41 @@ impl Libm for f32 {
         libm::ceilf(self)
     }
 
+    #[inline(always)]
+    fn round(self) -> Self {
+        libm::roundf(self)
+    }
+
     #[inline(always)]
     fn abs(self) -> Self {
         libm::fabsf(self)
     }
 
+    #[inline(always)]
+    fn sqrt(self) -> Self {
+        libm::sqrtf(self)
+    }
+
+    #[inline(always)]
+    fn exp(self) -> Self {
+        libm::expf(self)
+    }
+
     #[inline(always)]
     fn trunc(self) -> Self {
         libm::truncf(self)
     }
 
+    #[inline(always)]
+    fn log10(self) -> Self {
+        libm::log10f(self)
+    }
+
+    #[inline(always)]
+    fn ln(self) -> Self {
+        libm::logf(self)
+    }
+
     #[inline(always)]
     fn powi(mut self, n: i32) -> Self {
         match n {
@@ -103,11 +145,21 @@ impl Libm for f64 {
         libm::sin(self)
     }
 
+    #[inline(always)]
+    fn asin(self) -> Self {
+        libm::asin(self)
+    }
+
     #[inline(always)]
     fn cos(self) -> Self {
         libm::cos(self)
     }
 
+    #[inline(always)]
+    fn tan(self) -> Self {
+        libm::tan(self)
+    }
+
     #[inline(always)]
     fn floor(self) -> Self {
         libm::floor(self)
@@ -118,16 +170,41 @@ impl Libm for f64 {
         libm::ceil(self)
     }
 
+    #[inline(always)]
+    fn round(self) -> Self {
+        libm::round(self)
+    }
+
     #[inline(always)]
     fn abs(self) -> Self {
         libm::fabs(self)
     }
 
+    #[inline(always)]
+    fn sqrt(self) -> Self {
+        libm::sqrt(self)
+    }
+
+    #[inline(always)]
+    fn exp(self) -> Self {
+        libm::exp(self)
+    }
+
     #[inline(always)]
     fn trunc(self) -> Self {
         libm::trunc(self)
     }
 
+    #[inline(always)]
+    fn log10(self) -> Self {
+        libm::log10(self)
+    }
+
+    #[inline(always)]
+    fn ln(self) -> Self {
+        libm::log(self)
+    }
+
     #[inline(always)]
     fn powi(mut self, n: i32) -> Self {
         match n {
@@ -171,6 +248,16 @@ impl Libm for f64 {
     }
 }
 
+/// Coefficient of a one-pole smoother with a time constant of `ms`
+/// milliseconds at `hz` (zero responds instantly).
+pub(crate) fn smoothing(hz: u32, ms: f32) -> f32 {
+    if ms <= 0.0 {
+        0.0
+    } else {
+        (-1000.0 / (ms * hz as f32)).exp()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,116 @@
+// Copyright © 2020-2022 The Fon Contributors.
+//
+// Licensed under any of:
+// - Apache License, Version 2.0 (https://www.apache.org/licenses/LICENSE-2.0)
+// - MIT License (https://mit-license.org/)
+// - Boost Software License, Version 1.0 (https://www.boost.org/LICENSE_1_0.txt)
+// At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
+// LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
+
+//! Mixing many sources without intermediate clipping.
+
+use crate::chan::Channel;
+use crate::{Audio, Frame};
+
+use alloc::vec::Vec;
+use core::convert::TryInto;
+use core::num::NonZeroU32;
+
+/// Buffer that mixes audio by summing at 64-bit float precision, and only
+/// converts to a channel format at the end.
+///
+/// Summing many sources directly into integer frames (such as
+/// [`Ch16`](crate::chan::Ch16)) clips every time a partial sum goes out of
+/// range, even if the final mix wouldn't.  Accumulating here keeps the
+/// overshoot until [`finalize()`](Self::finalize).
+#[derive(Clone, Debug)]
+pub struct MixBuffer<const CH: usize> {
+    sample_rate: NonZeroU32,
+    frames: Vec<[f64; CH]>,
+}
+
+impl<const CH: usize> MixBuffer<CH> {
+    /// Create a new, empty mix buffer at `hz`.
+    pub fn new(hz: u32) -> Self {
+        Self {
+            sample_rate: hz.try_into().unwrap(),
+            frames: Vec::new(),
+        }
+    }
+
+    /// Get the length of the mix in frames (the length of the longest source
+    /// added).
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Check if nothing has been mixed in yet.
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Mix `audio` in from the start, extending the mix if it's longer.
+    ///
+    /// # Panics
+    /// If the sample rate doesn't match.
+    pub fn add<Chan: Channel>(&mut self, audio: &Audio<Chan, CH>) {
+        assert_eq!(self.sample_rate, audio.sample_rate());
+        if self.frames.len() < audio.len() {
+            self.frames.resize(audio.len(), [0.0; CH]);
+        }
+        for (sum, frame) in self.frames.iter_mut().zip(audio.iter()) {
+            for (sum, chan) in sum.iter_mut().zip(frame.channels()) {
+                *sum += f64::from(chan.to_f32());
+            }
+        }
+    }
+
+    /// Convert the mix to an `Audio` buffer, clipping any samples that are
+    /// still out of range.
+    pub fn finalize<Chan: Channel>(self) -> Audio<Chan, CH> {
+        let frames = self
+            .frames
+            .iter()
+            .map(|sums| {
+                let mut frame = Frame::<Chan, CH>::default();
+                for (chan, sum) in frame.channels_mut().iter_mut().zip(sums) {
+                    *chan = Chan::from(*sum as f32);
+                }
+                frame
+            })
+            .collect::<Vec<_>>();
+        Audio::with_frames(self.sample_rate.get(), frames)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chan::Ch16;
+
+    #[test]
+    fn cancels_without_clipping() {
+        let full = Audio::<Ch16, 2>::with_i16_buffer(48_000, [i16::MAX; 8]);
+        let negative = Audio::<Ch16, 2>::with_i16_buffer(48_000, [i16::MIN; 6]);
+
+        // Summing in 16-bit clips after the first two sources.
+        let mut direct = Audio::<Ch16, 2>::with_silence(48_000, 4);
+        for source in [&full, &full, &negative, &negative] {
+            for (sum, frame) in direct.iter_mut().zip(source.iter()) {
+                *sum = *sum + *frame;
+            }
+        }
+        assert_ne!(direct.get(0).unwrap().channels()[0], Ch16::MID);
+
+        let mut mix = MixBuffer::new(48_000);
+        for source in [&full, &full, &negative, &negative] {
+            mix.add(source);
+        }
+        assert_eq!(mix.len(), 4);
+        let mix = mix.finalize::<Ch16>();
+        for frame in &mix.as_slice()[..3] {
+            assert_eq!(frame.channels(), &[Ch16::MID; 2]);
+        }
+        assert_eq!(mix.get(3).unwrap().channels(), &[Ch16::MAX; 2]);
+    }
+}
@@ -23,6 +23,7 @@ pub struct Mono;
 ///  - 3.0
 ///  - 6.1
 ///  - 7.1
+///  - 7.1.4
 #[derive(Copy, Clone, Debug)]
 pub struct Left;
 
@@ -31,6 +32,7 @@ pub struct Left;
 ///  - 3.0
 ///  - 6.1
 ///  - 7.1
+///  - 7.1.4
 #[derive(Copy, Clone, Debug)]
 pub struct Right;
 
@@ -44,6 +46,7 @@ pub struct Center;
 ///  - 5.1
 ///  - 6.1
 ///  - 7.1
+///  - 7.1.4
 #[derive(Copy, Clone, Debug)]
 pub struct Front;
 
@@ -54,6 +57,7 @@ pub struct Front;
 ///  - 5.1
 ///  - 6.1
 ///  - 7.1
+///  - 7.1.4
 #[derive(Copy, Clone, Debug)]
 pub struct FrontL;
 
@@ -64,6 +68,7 @@ pub struct FrontL;
 ///  - 5.1
 ///  - 6.1
 ///  - 7.1
+///  - 7.1.4
 #[derive(Copy, Clone, Debug)]
 pub struct FrontR;
 
@@ -85,6 +90,7 @@ pub struct SurroundR;
 ///  - 5.1
 ///  - 6.1
 ///  - 7.1
+///  - 7.1.4
 #[derive(Copy, Clone, Debug)]
 pub struct Lfe;
 
@@ -95,14 +101,36 @@ pub struct Back;
 
 /// Back Left (150 degrees left)
 ///  - 7.1
+///  - 7.1.4
 #[derive(Copy, Clone, Debug)]
 pub struct BackL;
 
 /// Back Right (150 degrees right)
 ///  - 7.1
+///  - 7.1.4
 #[derive(Copy, Clone, Debug)]
 pub struct BackR;
 
+/// Top Front Left (45 degrees left, elevated)
+///  - 7.1.4
+#[derive(Copy, Clone, Debug)]
+pub struct TopFrontL;
+
+/// Top Front Right (45 degrees right, elevated)
+///  - 7.1.4
+#[derive(Copy, Clone, Debug)]
+pub struct TopFrontR;
+
+/// Top Back Left (135 degrees left, elevated)
+///  - 7.1.4
+#[derive(Copy, Clone, Debug)]
+pub struct TopBackL;
+
+/// Top Back Right (135 degrees right, elevated)
+///  - 7.1.4
+#[derive(Copy, Clone, Debug)]
+pub struct TopBackR;
+
 ////////////////////////////////////////////////////////////
 
 impl<Chan: Channel> Index<Mono> for Frame<Chan, 1> {
@@ -622,3 +650,173 @@ impl<Chan: Channel> IndexMut<Right> for Frame<Chan, 8> {
         &mut self.channels_mut()[7]
     }
 }
+
+////////////////////////////////////////////////////////////
+
+impl<Chan: Channel> Index<FrontL> for Frame<Chan, 12> {
+    type Output = Chan;
+
+    fn index(&self, _: FrontL) -> &Self::Output {
+        &self.channels()[0]
+    }
+}
+
+impl<Chan: Channel> IndexMut<FrontL> for Frame<Chan, 12> {
+    fn index_mut(&mut self, _: FrontL) -> &mut Self::Output {
+        &mut self.channels_mut()[0]
+    }
+}
+
+impl<Chan: Channel> Index<FrontR> for Frame<Chan, 12> {
+    type Output = Chan;
+
+    fn index(&self, _: FrontR) -> &Self::Output {
+        &self.channels()[1]
+    }
+}
+
+impl<Chan: Channel> IndexMut<FrontR> for Frame<Chan, 12> {
+    fn index_mut(&mut self, _: FrontR) -> &mut Self::Output {
+        &mut self.channels_mut()[1]
+    }
+}
+
+impl<Chan: Channel> Index<Front> for Frame<Chan, 12> {
+    type Output = Chan;
+
+    fn index(&self, _: Front) -> &Self::Output {
+        &self.channels()[2]
+    }
+}
+
+impl<Chan: Channel> IndexMut<Front> for Frame<Chan, 12> {
+    fn index_mut(&mut self, _: Front) -> &mut Self::Output {
+        &mut self.channels_mut()[2]
+    }
+}
+
+impl<Chan: Channel> Index<Lfe> for Frame<Chan, 12> {
+    type Output = Chan;
+
+    fn index(&self, _: Lfe) -> &Self::Output {
+        &self.channels()[3]
+    }
+}
+
+impl<Chan: Channel> IndexMut<Lfe> for Frame<Chan, 12> {
+    fn index_mut(&mut self, _: Lfe) -> &mut Self::Output {
+        &mut self.channels_mut()[3]
+    }
+}
+
+impl<Chan: Channel> Index<BackL> for Frame<Chan, 12> {
+    type Output = Chan;
+
+    fn index(&self, _: BackL) -> &Self::Output {
+        &self.channels()[4]
+    }
+}
+
+impl<Chan: Channel> IndexMut<BackL> for Frame<Chan, 12> {
+    fn index_mut(&mut self, _: BackL) -> &mut Self::Output {
+        &mut self.channels_mut()[4]
+    }
+}
+
+impl<Chan: Channel> Index<BackR> for Frame<Chan, 12> {
+    type Output = Chan;
+
+    fn index(&self, _: BackR) -> &Self::Output {
+        &self.channels()[5]
+    }
+}
+
+impl<Chan: Channel> IndexMut<BackR> for Frame<Chan, 12> {
+    fn index_mut(&mut self, _: BackR) -> &mut Self::Output {
+        &mut self.channels_mut()[5]
+    }
+}
+
+impl<Chan: Channel> Index<Left> for Frame<Chan, 12> {
+    type Output = Chan;
+
+    fn index(&self, _: Left) -> &Self::Output {
+        &self.channels()[6]
+    }
+}
+
+impl<Chan: Channel> IndexMut<Left> for Frame<Chan, 12> {
+    fn index_mut(&mut self, _: Left) -> &mut Self::Output {
+        &mut self.channels_mut()[6]
+    }
+}
+
+impl<Chan: Channel> Index<Right> for Frame<Chan, 12> {
+    type Output = Chan;
+
+    fn index(&self, _: Right) -> &Self::Output {
+        &self.channels()[7]
+    }
+}
+
+impl<Chan: Channel> IndexMut<Right> for Frame<Chan, 12> {
+    fn index_mut(&mut self, _: Right) -> &mut Self::Output {
+        &mut self.channels_mut()[7]
+    }
+}
+
+impl<Chan: Channel> Index<TopFrontL> for Frame<Chan, 12> {
+    type Output = Chan;
+
+    fn index(&self, _: TopFrontL) -> &Self::Output {
+        &self.channels()[8]
+    }
+}
+
+impl<Chan: Channel> IndexMut<TopFrontL> for Frame<Chan, 12> {
+    fn index_mut(&mut self, _: TopFrontL) -> &mut Self::Output {
+        &mut self.channels_mut()[8]
+    }
+}
+
+impl<Chan: Channel> Index<TopFrontR> for Frame<Chan, 12> {
+    type Output = Chan;
+
+    fn index(&self, _: TopFrontR) -> &Self::Output {
+        &self.channels()[9]
+    }
+}
+
+impl<Chan: Channel> IndexMut<TopFrontR> for Frame<Chan, 12> {
+    fn index_mut(&mut self, _: TopFrontR) -> &mut Self::Output {
+        &mut self.channels_mut()[9]
+    }
+}
+
+impl<Chan: Channel> Index<TopBackL> for Frame<Chan, 12> {
+    type Output = Chan;
+
+    fn index(&self, _: TopBackL) -> &Self::Output {
+        &self.channels()[10]
+    }
+}
+
+impl<Chan: Channel> IndexMut<TopBackL> for Frame<Chan, 12> {
+    fn index_mut(&mut self, _: TopBackL) -> &mut Self::Output {
+        &mut self.channels_mut()[10]
+    }
+}
+
+impl<Chan: Channel> Index<TopBackR> for Frame<Chan, 12> {
+    type Output = Chan;
+
+    fn index(&self, _: TopBackR) -> &Self::Output {
+        &self.channels()[11]
+    }
+}
+
+impl<Chan: Channel> IndexMut<TopBackR> for Frame<Chan, 12> {
+    fn index_mut(&mut self, _: TopBackR) -> &mut Self::Output {
+        &mut self.channels_mut()[11]
+    }
+}
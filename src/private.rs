@@ -7,10 +7,11 @@
 // At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
 // LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
 
-use crate::chan::{Ch16, Ch24, Ch32, Ch64};
+use crate::chan::{Ch16, Ch24, Ch32, Ch32i, Ch64};
 
 pub trait Sealed {}
 impl Sealed for Ch16 {}
 impl Sealed for Ch24 {}
 impl Sealed for Ch32 {}
 impl Sealed for Ch64 {}
+impl Sealed for Ch32i {}
@@ -7,10 +7,16 @@
 // At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
 // LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
 
+use_libm!();
+
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
 use core::fmt::Debug;
+use core::iter;
 use core::num::NonZeroU32;
 
 use crate::chan::Channel;
+use crate::math::smoothing;
 use crate::Frame;
 
 /// Audio sink - a type that consumes audio samples.
@@ -123,3 +129,253 @@ where
         self.sink.sink_with(&mut iter.map(Frame::to))
     }
 }
+
+/// Time for the gain of a [`LimiterSink`] to recover (in milliseconds).
+const LIMITER_RELEASE_MS: f32 = 50.0;
+
+/// Sink that limits the peak level of audio passing through to another
+/// [`Sink`](crate::Sink), so live output never goes over a ceiling.
+///
+/// Audio is delayed by a short look-ahead, so the gain can ramp down before a
+/// peak arrives rather than distorting it.  The gain recovers over about
+/// 50 ms once the peak has passed.
+#[derive(Debug)]
+pub struct LimiterSink<Chan, S, const CH: usize>
+where
+    Chan: Channel,
+    S: Sink<Chan, CH>,
+{
+    sink: S,
+    limiter: Limiter<Chan, CH>,
+}
+
+impl<Chan, S, const CH: usize> LimiterSink<Chan, S, CH>
+where
+    Chan: Channel,
+    S: Sink<Chan, CH>,
+{
+    /// Limit audio going to `sink` to `ceiling_db` (usually just under 0),
+    /// looking ahead by `lookahead_ms` milliseconds.
+    pub fn new(sink: S, ceiling_db: f32, lookahead_ms: f32) -> Self {
+        let hz = sink.sample_rate().get();
+        let lookahead =
+            ((lookahead_ms * hz as f32 / 1000.0).round() as usize).max(1);
+        let limiter = Limiter {
+            ceiling: (ceiling_db / 20.0 * core::f32::consts::LN_10).exp(),
+            lookahead,
+            delay: VecDeque::with_capacity(lookahead + 1),
+            gain: 1.0,
+            // Mostly reach the target gain within the look-ahead.
+            attack: smoothing(hz, lookahead_ms / 4.0),
+            release: smoothing(hz, LIMITER_RELEASE_MS),
+        };
+        Self { sink, limiter }
+    }
+
+    /// Get the look-ahead delay in frames.
+    pub fn latency(&self) -> usize {
+        self.limiter.lookahead
+    }
+
+    /// Push the audio still held back by the look-ahead out to the sink.
+    pub fn flush(&mut self) {
+        let mut silence =
+            iter::repeat(Frame::default()).take(self.limiter.lookahead);
+        self.sink.sink_with(&mut Limited {
+            limiter: &mut self.limiter,
+            iter: &mut silence,
+        });
+    }
+
+    /// Get back the wrapped sink.
+    pub fn into_inner(self) -> S {
+        self.sink
+    }
+}
+
+impl<Chan, S, const CH: usize> Sink<Chan, CH> for LimiterSink<Chan, S, CH>
+where
+    Chan: Channel,
+    S: Sink<Chan, CH>,
+{
+    fn sample_rate(&self) -> NonZeroU32 {
+        self.sink.sample_rate()
+    }
+
+    fn len(&self) -> usize {
+        self.sink.len()
+    }
+
+    fn sink_with(&mut self, iter: &mut dyn Iterator<Item = Frame<Chan, CH>>) {
+        self.sink.sink_with(&mut Limited {
+            limiter: &mut self.limiter,
+            iter,
+        });
+    }
+}
+
+/// Look-ahead peak limiter state for a [`LimiterSink`].
+#[derive(Debug)]
+struct Limiter<Chan: Channel, const CH: usize> {
+    /// Linear peak ceiling.
+    ceiling: f32,
+    /// Look-ahead in frames.
+    lookahead: usize,
+    /// Delayed frames, and the gain each needs to stay under the ceiling.
+    delay: VecDeque<(Frame<Chan, CH>, f32)>,
+    /// Current (smoothed) gain.
+    gain: f32,
+    attack: f32,
+    release: f32,
+}
+
+impl<Chan: Channel, const CH: usize> Limiter<Chan, CH> {
+    /// Push a frame into the look-ahead, returning the limited frame that
+    /// comes out of it, if full.
+    fn push(&mut self, frame: Frame<Chan, CH>) -> Option<Frame<Chan, CH>> {
+        let peak = frame
+            .channels()
+            .iter()
+            .map(|chan| chan.to_f32().abs())
+            .fold(0.0, f32::max);
+        let needed = if peak > self.ceiling {
+            self.ceiling / peak
+        } else {
+            1.0
+        };
+        self.delay.push_back((frame, needed));
+        if self.delay.len() <= self.lookahead {
+            return None;
+        }
+
+        let (mut frame, needed) = self.delay.pop_front()?;
+        let target = self.delay.iter().map(|(_, g)| *g).fold(needed, f32::min);
+        let coefficient = if target < self.gain {
+            self.attack
+        } else {
+            self.release
+        };
+        self.gain = coefficient * self.gain + (1.0 - coefficient) * target;
+        // Never let the smoothing overshoot the ceiling.
+        frame.gain(self.gain.min(needed));
+        Some(frame)
+    }
+}
+
+/// Iterator passing frames through a [`Limiter`].
+struct Limited<'a, 'b, Chan: Channel, const CH: usize> {
+    limiter: &'a mut Limiter<Chan, CH>,
+    iter: &'b mut dyn Iterator<Item = Frame<Chan, CH>>,
+}
+
+impl<Chan: Channel, const CH: usize> Iterator for Limited<'_, '_, Chan, CH> {
+    type Item = Frame<Chan, CH>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(frame) = self.limiter.push(self.iter.next()?) {
+                return Some(frame);
+            }
+        }
+    }
+}
+
+/// Sink that appends up to a fixed number of frames to a `Vec`.
+#[derive(Debug)]
+pub(crate) struct VecSink<'a, Chan: Channel, const CH: usize> {
+    hz: NonZeroU32,
+    room: usize,
+    frames: &'a mut Vec<Frame<Chan, CH>>,
+}
+
+impl<'a, Chan: Channel, const CH: usize> VecSink<'a, Chan, CH> {
+    /// Create a sink appending at most `room` frames to `frames`.
+    pub(crate) fn new(
+        hz: NonZeroU32,
+        room: usize,
+        frames: &'a mut Vec<Frame<Chan, CH>>,
+    ) -> Self {
+        Self { hz, room, frames }
+    }
+}
+
+impl<Chan: Channel, const CH: usize> Sink<Chan, CH> for VecSink<'_, Chan, CH> {
+    fn sample_rate(&self) -> NonZeroU32 {
+        self.hz
+    }
+
+    fn len(&self) -> usize {
+        self.room
+    }
+
+    fn sink_with(&mut self, iter: &mut dyn Iterator<Item = Frame<Chan, CH>>) {
+        let start = self.frames.len();
+        self.frames.extend(iter.take(self.room));
+        self.room -= self.frames.len() - start;
+    }
+}
+
+/// Sink that forwards to a borrowed sink, so it can be piped to repeatedly.
+#[derive(Debug)]
+pub(crate) struct SinkRef<'a, S>(pub(crate) &'a mut S);
+
+impl<Chan, S, const CH: usize> Sink<Chan, CH> for SinkRef<'_, S>
+where
+    Chan: Channel,
+    S: Sink<Chan, CH>,
+{
+    fn sample_rate(&self) -> NonZeroU32 {
+        self.0.sample_rate()
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn sink_with(&mut self, iter: &mut dyn Iterator<Item = Frame<Chan, CH>>) {
+        self.0.sink_with(iter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chan::Ch32;
+
+    #[test]
+    fn limiter_sink() {
+        let frames = (0..4800)
+            .map(|i| {
+                let level = if i < 2400 { 0.5 } else { 1.5 };
+                let x = level * (i as f32 * 0.05).sin();
+                Frame::<Ch32, 2>::new(Ch32::new(x), Ch32::new(-x))
+            })
+            .collect::<Vec<_>>();
+
+        let hz = NonZeroU32::new(48_000).unwrap();
+        let mut output = Vec::new();
+        let mut limiter =
+            LimiterSink::new(VecSink::new(hz, 10_000, &mut output), -1.0, 2.0);
+        assert_eq!(limiter.latency(), 96);
+        for chunk in frames.chunks(1000) {
+            limiter.sink_with(&mut chunk.iter().cloned());
+        }
+        limiter.flush();
+        drop(limiter);
+
+        assert_eq!(output.len(), frames.len());
+        let ceiling = 10.0f32.powf(-1.0 / 20.0);
+        let peak = output
+            .iter()
+            .flat_map(|frame| frame.channels().iter())
+            .map(|chan| chan.to_f32().abs())
+            .fold(0.0, f32::max);
+        assert!(peak <= ceiling + 1e-6);
+        assert!(peak > ceiling * 0.95);
+        // Audio under the ceiling passes through.
+        for (out, frame) in output[..2000].iter().zip(frames.iter()) {
+            let (out, x) = (out.channels()[0], frame.channels()[0]);
+            assert!((out.to_f32() - x.to_f32()).abs() < 1e-5);
+        }
+    }
+}
@@ -0,0 +1,227 @@
+// Copyright © 2020-2022 The Fon Contributors.
+//
+// Licensed under any of:
+// - Apache License, Version 2.0 (https://www.apache.org/licenses/LICENSE-2.0)
+// - MIT License (https://mit-license.org/)
+// - Boost Software License, Version 1.0 (https://www.boost.org/LICENSE_1_0.txt)
+// At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
+// LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
+
+//! Running audio statistics.
+
+use_libm!();
+
+use crate::chan::Channel;
+use crate::math::smoothing;
+use crate::{Audio, Frame};
+
+/// Aggregate statistics for each channel of some audio.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Stats<const CH: usize> {
+    /// Peak absolute sample value of each channel.
+    pub peak: [f32; CH],
+    /// Root mean square of each channel.
+    pub rms: [f32; CH],
+    /// Number of frames.
+    pub count: usize,
+}
+
+/// Accumulates [`Stats`] incrementally, without holding on to the audio.
+#[derive(Copy, Clone, Debug)]
+pub struct StatsAccumulator<const CH: usize> {
+    peak: [f32; CH],
+    sum_sq: [f64; CH],
+    count: usize,
+}
+
+impl<const CH: usize> Default for StatsAccumulator<CH> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const CH: usize> StatsAccumulator<CH> {
+    /// Create a new accumulator, with no frames pushed.
+    pub fn new() -> Self {
+        Self {
+            peak: [0.0; CH],
+            sum_sq: [0.0; CH],
+            count: 0,
+        }
+    }
+
+    /// Push a single frame.
+    pub fn push_frame<Chan: Channel>(&mut self, frame: &Frame<Chan, CH>) {
+        for (i, chan) in frame.channels().iter().enumerate() {
+            let sample = chan.to_f32();
+            self.peak[i] = self.peak[i].max(sample.abs());
+            self.sum_sq[i] += f64::from(sample) * f64::from(sample);
+        }
+        self.count += 1;
+    }
+
+    /// Push a chunk of audio.
+    pub fn push_audio<Chan: Channel>(&mut self, audio: &Audio<Chan, CH>) {
+        for frame in audio.iter() {
+            self.push_frame(frame);
+        }
+    }
+
+    /// Get the aggregate statistics of all frames pushed so far.
+    pub fn finish(&self) -> Stats<CH> {
+        let mut rms = [0.0; CH];
+        if self.count != 0 {
+            for (rms, sum_sq) in rms.iter_mut().zip(self.sum_sq.iter()) {
+                *rms = (sum_sq / self.count as f64).sqrt() as f32;
+            }
+        }
+        Stats {
+            peak: self.peak,
+            rms,
+            count: self.count,
+        }
+    }
+}
+
+/// Level meter for live display, with the ballistics of a hardware meter.
+///
+/// Unlike [`Stats`], which weighs every frame equally, the RMS level here
+/// is exponentially averaged with a time constant of `rms_ms`, and the peak
+/// level rises and falls with separate attack and release times, so the
+/// readings follow the audio like a VU meter or PPM (peak programme meter)
+/// needle.
+#[derive(Copy, Clone, Debug)]
+pub struct Meter<const CH: usize> {
+    rms_coeff: f32,
+    attack_coeff: f32,
+    release_coeff: f32,
+    mean_sq: [f32; CH],
+    peak: [f32; CH],
+}
+
+impl<const CH: usize> Meter<CH> {
+    /// Create a new meter for audio at `hz`.
+    ///
+    /// The RMS level is integrated with a time constant of `rms_ms`, and the
+    /// peak level rises with a time constant of `attack_ms` and falls with
+    /// one of `release_ms` (all in milliseconds; zero is instant).
+    pub fn new(hz: u32, rms_ms: f32, attack_ms: f32, release_ms: f32) -> Self {
+        Self {
+            rms_coeff: smoothing(hz, rms_ms),
+            attack_coeff: smoothing(hz, attack_ms),
+            release_coeff: smoothing(hz, release_ms),
+            mean_sq: [0.0; CH],
+            peak: [0.0; CH],
+        }
+    }
+
+    /// Create a meter with VU ballistics, where both the RMS and peak levels
+    /// take 300 ms to respond.
+    pub fn vu(hz: u32) -> Self {
+        Self::new(hz, 300.0, 300.0, 300.0)
+    }
+
+    /// Create a meter with PPM ballistics, where the peak level jumps up
+    /// instantly and falls back by 20 dB in about 2.8 seconds.
+    pub fn ppm(hz: u32) -> Self {
+        Self::new(hz, 300.0, 0.0, 2800.0 / core::f32::consts::LN_10)
+    }
+
+    /// Feed a single frame into the meter.
+    pub fn push_frame<Chan: Channel>(&mut self, frame: &Frame<Chan, CH>) {
+        for (i, chan) in frame.channels().iter().enumerate() {
+            let sample = chan.to_f32().abs();
+            let square = sample * sample;
+            self.mean_sq[i] =
+                square + (self.mean_sq[i] - square) * self.rms_coeff;
+            let coeff = if sample > self.peak[i] {
+                self.attack_coeff
+            } else {
+                self.release_coeff
+            };
+            self.peak[i] = sample + (self.peak[i] - sample) * coeff;
+        }
+    }
+
+    /// Feed a chunk of audio into the meter.
+    pub fn push_audio<Chan: Channel>(&mut self, audio: &Audio<Chan, CH>) {
+        for frame in audio.iter() {
+            self.push_frame(frame);
+        }
+    }
+
+    /// Get the current RMS level of each channel.
+    pub fn rms(&self) -> [f32; CH] {
+        let mut rms = [0.0; CH];
+        for (rms, mean_sq) in rms.iter_mut().zip(self.mean_sq.iter()) {
+            *rms = mean_sq.sqrt();
+        }
+        rms
+    }
+
+    /// Get the current peak level of each channel.
+    pub fn peak(&self) -> [f32; CH] {
+        self.peak
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chan::{Ch16, Ch32};
+    use alloc::vec::Vec;
+
+    #[test]
+    fn chunked() {
+        let frames = (0..1000)
+            .map(|i| {
+                let x = i as f32 * 0.01;
+                Frame::<Ch16, 2>::new(Ch16::from(x.sin()), Ch16::from(x.cos()))
+            })
+            .collect::<Vec<_>>();
+
+        let mut whole = StatsAccumulator::new();
+        whole.push_audio(&Audio::with_frames(48_000, frames.as_slice()));
+        let whole = whole.finish();
+
+        let mut chunked = StatsAccumulator::new();
+        for chunk in frames.chunks(137) {
+            chunked.push_audio(&Audio::with_frames(48_000, chunk));
+        }
+        assert_eq!(chunked.finish(), whole);
+        assert_eq!(whole.count, 1000);
+        assert!((whole.rms[0] - core::f32::consts::FRAC_1_SQRT_2).abs() < 0.05);
+        assert!(whole.peak[1] > 0.99);
+    }
+
+    #[test]
+    fn meter_ballistics() {
+        let burst =
+            Audio::<Ch32, 2>::with_f32_buffer(48_000, [0.8, 0.0].repeat(480));
+        let silence = Audio::<Ch32, 2>::with_silence(48_000, 4_800);
+
+        // The PPM jumps up on the first loud frame.
+        let mut ppm = Meter::ppm(48_000);
+        let mut vu = Meter::vu(48_000);
+        ppm.push_frame(&burst.get(0).unwrap());
+        vu.push_frame(&burst.get(0).unwrap());
+        assert!((ppm.peak()[0] - 0.8).abs() < 0.001);
+        assert!(vu.peak()[0] < 0.01);
+        assert_eq!(ppm.peak()[1], 0.0);
+
+        // Then falls slowly: about 20 dB in 2.8 seconds.
+        ppm.push_audio(&burst);
+        ppm.push_audio(&silence);
+        assert!(ppm.peak()[0] > 0.7);
+        for _ in 1..28 {
+            ppm.push_audio(&silence);
+        }
+        assert!((ppm.peak()[0] - 0.08).abs() < 0.005, "{}", ppm.peak()[0]);
+
+        // The RMS level settles on that of the burst.
+        for _ in 0..150 {
+            vu.push_audio(&burst);
+        }
+        assert!((vu.rms()[0] - 0.8).abs() < 0.01, "{}", vu.rms()[0]);
+    }
+}
@@ -7,19 +7,40 @@
 // At your choosing (See accompanying files LICENSE_APACHE_2_0.txt,
 // LICENSE_MIT.txt and LICENSE_BOOST_1_0.txt).
 
+use_libm!();
+
 use alloc::vec::Vec;
+use core::marker::PhantomData;
 use core::mem;
 use core::num::NonZeroU32;
 
 use crate::chan::{Ch32, Channel};
 use crate::frame::Frame;
+use crate::sink::{SinkRef, VecSink};
 use crate::{Audio, Sink};
 
 mod speex;
 
+pub(crate) use speex::sinc;
 use speex::ResamplerState;
 
-const WINDOW_FN_KAISER_TABLE: &[f64] = &[
+const WINDOW_FN_KAISER6_TABLE: &[f64] = &[
+    0.99733006, 1.0, 0.99733006, 0.98935595, 0.97618418, 0.95799003,
+    0.93501423, 0.90755855, 0.87598009, 0.84068475, 0.80211977, 0.76076565,
+    0.71712752, 0.67172623, 0.62508937, 0.57774224, 0.53019925, 0.48295561,
+    0.43647969, 0.39120616, 0.34752997, 0.30580127, 0.26632152, 0.22934058,
+    0.19505503, 0.16360756, 0.13508755, 0.10953262, 0.0869312, 0.067226,
+    0.0503182, 0.03607231, 0.02432151, 0.01487334, 0.00752, 0.0,
+];
+const WINDOW_FN_KAISER8_TABLE: &[f64] = &[
+    0.99635258, 1.0, 0.99635258, 0.98548012, 0.96759014, 0.943022, 0.91223751,
+    0.87580811, 0.83439927, 0.78875245, 0.73966538, 0.68797126, 0.6345175,
+    0.58014482, 0.52566725, 0.47185369, 0.4194115, 0.36897272, 0.32108304,
+    0.27619388, 0.23465776, 0.1967267, 0.1625538, 0.13219758, 0.10562887,
+    0.08273982, 0.06335451, 0.04724088, 0.03412321, 0.0236949, 0.01563093,
+    0.00959968, 0.00527363, 0.00233883, 0.0005, 0.0,
+];
+const WINDOW_FN_KAISER10_TABLE: &[f64] = &[
     0.99537781, 1.0, 0.99537781, 0.98162644, 0.95908712, 0.92831446,
     0.89005583, 0.84522401, 0.79486424, 0.74011713, 0.68217934, 0.62226347,
     0.56155915, 0.5011968, 0.44221549, 0.38553619, 0.33194107, 0.28205962,
@@ -29,6 +50,87 @@ const WINDOW_FN_KAISER_TABLE: &[f64] = &[
 ];
 const WINDOW_FN_OVERSAMPLE: usize = 32;
 
+/// Window function used to shape the resampling filter.
+///
+/// Windows with a higher Kaiser β attenuate aliasing more, at the cost of a
+/// wider transition band (so the highest frequencies below Nyquist are cut
+/// more).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Window {
+    /// Kaiser window with β = 6.
+    Kaiser6,
+    /// Kaiser window with β = 8.
+    Kaiser8,
+    /// Kaiser window with β = 10 (the default).
+    Kaiser10,
+}
+
+impl Default for Window {
+    fn default() -> Self {
+        Window::Kaiser10
+    }
+}
+
+impl Window {
+    /// Get the precomputed table (oversampled by `WINDOW_FN_OVERSAMPLE`).
+    fn table(self) -> &'static [f64] {
+        match self {
+            Window::Kaiser6 => WINDOW_FN_KAISER6_TABLE,
+            Window::Kaiser8 => WINDOW_FN_KAISER8_TABLE,
+            Window::Kaiser10 => WINDOW_FN_KAISER10_TABLE,
+        }
+    }
+}
+
+/// Resampling filter for one pair of sample rates, computed once and shared
+/// between any number of [`Stream`]s.
+///
+/// Building a stream with [`Stream::with_filter()`] only clones a reference
+/// to the filter's sinc table, rather than computing it again, which adds up
+/// when transcoding many files at the same sample rates.
+#[derive(Clone, Debug)]
+pub struct ResamplerFilter {
+    /// Source sample rate.
+    input_sample_rate: NonZeroU32,
+    /// Target sample rate.
+    output_sample_rate: u32,
+    /// Simplified ratio of input ÷ output samples.
+    ratio: (u32, u32),
+    /// Channel state for a new stream, sharing the sinc table.
+    state: ResamplerState,
+}
+
+impl ResamplerFilter {
+    /// Compute the filter for resampling from `src_hz` to `dst_hz`, with a
+    /// `quality` from 0 (fastest) to 10 (best).
+    ///
+    /// # Panics
+    /// If either sample rate is zero, or `quality` is greater than 10.
+    pub fn new(src_hz: u32, dst_hz: u32, quality: u8) -> Self {
+        assert_ne!(dst_hz, 0);
+        assert!(
+            usize::from(quality) <= speex::MAX_QUALITY,
+            "Quality {} out of range 0..=10",
+            quality
+        );
+        let input_sample_rate = NonZeroU32::new(src_hz).unwrap();
+        let ratio = simplify(src_hz, dst_hz);
+        let mut state = ResamplerState {
+            quality: quality.into(),
+            ..Default::default()
+        };
+        if src_hz != dst_hz {
+            state.update_filter(ratio.0, ratio.1);
+        }
+        Self {
+            input_sample_rate,
+            output_sample_rate: dst_hz,
+            ratio,
+            state,
+        }
+    }
+}
+
 /// Stream resampler.
 #[derive(Debug)]
 pub struct Stream<const CH: usize> {
@@ -39,9 +141,15 @@ pub struct Stream<const CH: usize> {
     /// Simplified ratio of input ÷ output samples.
     ratio: (u32, u32),
     /// Channel data.
-    channels: [Resampler32; 8],
+    channels: [Resampler32; CH],
     /// Calculated input latency for resampler.
     input_latency: u32,
+    /// Expected number of output frames for the audio resampled so far.
+    resampled_len: f64,
+    /// Whether to pick the quality from the ratio (see `new_auto()`).
+    auto_quality: bool,
+    /// Reused output buffer for `push_frame()`.
+    frame_output: Vec<Frame<Ch32, CH>>,
 }
 
 impl<const CH: usize> Stream<CH> {
@@ -52,48 +160,271 @@ impl<const CH: usize> Stream<CH> {
             output_sample_rate: target_hz,
             input_sample_rate: None,
             ratio: (0, 1),
-            channels: [
-                Default::default(),
-                Default::default(),
-                Default::default(),
-                Default::default(),
-                Default::default(),
-                Default::default(),
-                Default::default(),
-                Default::default(),
-            ],
+            channels: [(); CH].map(|_| Resampler32::default()),
             input_latency: 0,
+            resampled_len: 0.0,
+            auto_quality: false,
+            frame_output: Vec::new(),
         }
     }
 
+    /// Create a new stream at target sample rate, which picks the resampling
+    /// quality to suit the ratio between sample rates once the source sample
+    /// rate is known.
+    ///
+    /// The heuristic is:
+    ///  - Downsampling by a factor of 2 or more uses quality 10, because
+    ///    everything above the new Nyquist frequency must be filtered out
+    ///    without aliasing.
+    ///  - Ratios within 10% of 1 (like 48 kHz to 44.1 kHz) use quality 5,
+    ///    since little content is near the cutoff.
+    ///  - Anything else uses the default quality of 8.
+    ///
+    /// The quality is picked again whenever the source sample rate changes,
+    /// until it is set with
+    /// [`set_channel_quality()`](Self::set_channel_quality).
+    pub fn new_auto(target_hz: u32) -> Self {
+        let mut stream = Self::new(target_hz);
+        stream.auto_quality = true;
+        stream
+    }
+
+    /// Create a new stream with both the source and target sample rates known
+    /// up front.
+    ///
+    /// Unlike [`new()`](Self::new), the resampling filter is set up
+    /// immediately, rather than on the first [`pipe()`](Self::pipe), so
+    /// [`input_latency()`](Self::input_latency) and
+    /// [`output_latency()`](Self::output_latency) are known before any audio
+    /// flows.  If the sample rates are the same, audio is copied without
+    /// latency.
+    ///
+    /// # Panics
+    /// If either sample rate is zero.
+    pub fn with_rates(src_hz: u32, dst_hz: u32) -> Self {
+        let mut stream = Self::new(dst_hz);
+        let src_hz = NonZeroU32::new(src_hz).unwrap();
+        if src_hz.get() != dst_hz {
+            stream.source_hz(src_hz);
+        }
+        stream
+    }
+
+    /// Create a new stream from a source at the fractional sample rate of
+    /// `num / den` hertz (like 44100.5 Hz as `88201 / 2`) to `target_hz`, for
+    /// precise varispeed.
+    ///
+    /// The ratio between the sample rates is kept exact, rather than rounding
+    /// the source sample rate to a whole hertz.  Audio piped in should be at
+    /// the source sample rate rounded to the nearest hertz, since any other
+    /// sample rate replaces the fractional one; or use
+    /// [`process()`](Self::process).
+    ///
+    /// # Panics
+    /// If `den` or `target_hz` is zero, the source sample rate rounds to
+    /// zero, or `den * target_hz` (after simplifying) overflows a `u32`.
+    pub fn new_frac(num: u32, den: u32, target_hz: u32) -> Self {
+        let mut stream = Self::new(target_hz);
+        assert_ne!(den, 0);
+        let hz = (u64::from(num) + u64::from(den) / 2) / u64::from(den);
+        let hz = NonZeroU32::new(hz as u32).expect("Source sample rate is 0");
+
+        // Simplified ratio of input ÷ output samples, num ÷ (den × target).
+        let (num, den) = simplify(num, den);
+        let factor = gcd(num, target_hz);
+        let out = den.checked_mul(target_hz / factor).expect("Ratio overflow");
+        stream.source_ratio(hz, (num / factor, out));
+        stream
+    }
+
+    /// Create a new stream from a shared [`ResamplerFilter`].
+    ///
+    /// This resamples the same as [`with_rates()`](Self::with_rates) with
+    /// every channel set to the filter's quality, without recomputing the
+    /// filter.
+    pub fn with_filter(filter: &ResamplerFilter) -> Self {
+        let mut stream = Self::new(filter.output_sample_rate);
+        if filter.input_sample_rate.get() != filter.output_sample_rate {
+            for ch in stream.channels.iter_mut() {
+                ch.state = filter.state.clone();
+            }
+            stream.ratio = filter.ratio;
+            stream.input_sample_rate = Some(filter.input_sample_rate);
+            stream.update_latency();
+        }
+        stream
+    }
+
+    /// Get the latency of the resampler, in source sample rate frames.
+    ///
+    /// This is zero until the source sample rate is known.
+    pub fn input_latency(&self) -> u32 {
+        self.input_latency
+    }
+
+    /// Get the latency of the resampler, in target sample rate frames
+    /// (rounded to the nearest frame).
+    ///
+    /// This is zero until the source sample rate is known.
+    pub fn output_latency(&self) -> u32 {
+        let (num, den) = self.ratio;
+        if num == 0 {
+            return 0;
+        }
+        let latency = u64::from(self.input_latency) * u64::from(den);
+        ((latency + u64::from(num) / 2) / u64::from(num)) as u32
+    }
+
+    /// Get the simplified input and output sample rates the resampler is
+    /// actually using, as `(input, output)`.
+    ///
+    /// Only their ratio matters, so they may differ from the requested rates
+    /// (they're divided by their greatest common divisor, and a fractional
+    /// source rate from [`new_frac()`](Self::new_frac) is scaled up to a whole
+    /// number).  This is `(0, 1)` until the source sample rate is known, and
+    /// while the audio is passed through without resampling.
+    pub fn effective_rates(&self) -> (u32, u32) {
+        self.ratio
+    }
+
+    /// Check if audio at the target sample rate is copied straight through,
+    /// without resampling.
+    ///
+    /// This is true until audio at a different sample rate is resampled.
+    /// After that, audio even at the target sample rate keeps going through
+    /// the resampler, because its filter holds the last few frames of earlier
+    /// audio; a copy would drop those, and jump back in time by the latency.
+    pub fn is_passthrough(&self) -> bool {
+        self.channels[0].state.started == 0
+    }
+
     /// Switch source sample rate.
     fn source_hz(&mut self, hz: NonZeroU32) {
-        // Calculate new simplified ratio of input ÷ output samples.
-        let ratio = simplify(hz.get(), self.output_sample_rate);
-        let (num, den) = ratio;
-
         // Handle sample rate change, if needed.
         if NonZeroU32::new(hz.get()) != self.input_sample_rate {
-            // Prepare each channel for sample rate change
+            // Calculate new simplified ratio of input ÷ output samples.
+            let ratio = simplify(hz.get(), self.output_sample_rate);
+            self.source_ratio(hz, ratio);
+        }
+    }
+
+    /// Switch the ratio of input ÷ output samples, for a source sample rate of
+    /// about `hz`.
+    fn source_ratio(&mut self, hz: NonZeroU32, ratio: (u32, u32)) {
+        let (num, den) = ratio;
+        if self.auto_quality {
+            let quality = auto_quality(hz.get(), self.output_sample_rate);
             for ch in self.channels.iter_mut() {
-                // Store fractional sample data.
-                let v = ch.state.samp_frac_num;
-                ch.state.samp_frac_num = speex::_muldiv(v, den, self.ratio.1);
-                if ch.state.samp_frac_num >= den {
-                    ch.state.samp_frac_num = den - 1;
-                }
+                ch.state.quality = quality;
+            }
+        }
+        // Prepare each channel for sample rate change
+        for ch in self.channels.iter_mut() {
+            // Store fractional sample data.
+            let v = ch.state.samp_frac_num;
+            ch.state.samp_frac_num = speex::_muldiv(v, den, self.ratio.1);
+            if ch.state.samp_frac_num >= den {
+                ch.state.samp_frac_num = den - 1;
+            }
+
+            // Update filter.
+            ch.state.update_filter(num, den);
+        }
+        self.ratio = ratio;
+        self.input_sample_rate = Some(hz);
+        self.update_latency();
+    }
+
+    /// Set the resampling quality of one channel, from 0 (fastest) to 10
+    /// (best).  The default is 8.
+    ///
+    /// Lower qualities use a shorter filter, which is cheaper but lets more
+    /// aliasing through.  This suits channels that only carry low frequencies,
+    /// like the LFE channel (index 3 in 5.1 audio).  Channels stay aligned in
+    /// time, and [`input_latency()`](Self::input_latency) is that of the
+    /// longest filter.
+    ///
+    /// # Panics
+    /// If `channel` is out of bounds, or `quality` is greater than 10.
+    pub fn set_channel_quality(&mut self, channel: usize, quality: u8) {
+        assert!(channel < CH, "Channel {} out of bounds", channel);
+        assert!(
+            usize::from(quality) <= speex::MAX_QUALITY,
+            "Quality {} out of range 0..=10",
+            quality
+        );
+
+        self.auto_quality = false;
+        let state = &mut self.channels[channel].state;
+        state.quality = quality.into();
+        if self.input_sample_rate.is_some() {
+            state.update_filter(self.ratio.0, self.ratio.1);
+            self.update_latency();
+        }
+    }
 
-                // Update filter and calculate input latency.
-                ch.state.update_filter(num, den);
-                self.input_latency = ch.state.filt_len / 2;
+    /// Set the window function of the resampling filter for all channels.
+    pub fn set_window(&mut self, window: Window) {
+        for ch in self.channels.iter_mut() {
+            ch.state.window = window;
+            if self.input_sample_rate.is_some() {
+                ch.state.update_filter(self.ratio.0, self.ratio.1);
             }
-            self.ratio = ratio;
-            self.input_sample_rate = Some(hz);
         }
     }
 
+    /// Calculate input latency from the longest channel filter.
+    fn update_latency(&mut self) {
+        self.input_latency = self
+            .channels
+            .iter()
+            .map(|ch| ch.state.filt_len / 2)
+            .max()
+            .unwrap_or(0);
+    }
+
     /// Flush audio to sink and end stream.
+    ///
+    /// The resampler's latency is skipped at the start of the stream, so the
+    /// total output length is exactly
+    /// [`resampled_len()`](Self::resampled_len), with no trailing silence.
     pub fn flush<Ch, S>(mut self, sink: S)
+    where
+        Ch: Channel,
+        S: Sink<Ch, CH>,
+    {
+        self.drain(sink);
+    }
+
+    /// Get the number of frames the audio resampled so far should take up at
+    /// the target sample rate (rounded up).
+    pub fn resampled_len(&self) -> usize {
+        // Remove floating point error before rounding up.
+        let len = self.resampled_len.round();
+        if (self.resampled_len - len).abs() < 1e-6 {
+            len as usize
+        } else {
+            self.resampled_len.ceil() as usize
+        }
+    }
+
+    /// Turn this stream into a [`Transcoder`] that owns `sink`, so audio can
+    /// be pushed through without passing the sink in every time.
+    pub fn into_transcoder<Chan, S>(self, sink: S) -> Transcoder<Chan, S, CH>
+    where
+        Chan: Channel,
+        S: Sink<Chan, CH>,
+    {
+        assert_eq!(sink.sample_rate().get(), self.output_sample_rate);
+        Transcoder {
+            stream: self,
+            sink,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Resample the remaining buffered audio out to the sink.
+    fn drain<Ch, S>(&mut self, sink: S)
     where
         Ch: Channel,
         S: Sink<Ch, CH>,
@@ -118,9 +449,15 @@ impl<const CH: usize> Stream<CH> {
 
     /// Pipe audio through this stream, and out to the sink.
     ///
-    /// If the sink gets full, then no more audio will be written.  If there is
-    /// not enough audio then the sink chooses whether or not to fill the rest
-    /// of it's buffer with silence.
+    /// If the sink gets full, up to [`output_latency()`](Self::output_latency)
+    /// frames (at least one) of the rest of the resampled audio stay buffered
+    /// in the stream, and are written first by the next call (or by
+    /// [`flush()`](Self::flush)), so a sink that's off by a frame from
+    /// rounding doesn't lose audio; anything past that is dropped.  While the
+    /// sample rates match and nothing has been resampled yet, audio that
+    /// doesn't fit is dropped instead.  If
+    /// there is not enough audio then the sink chooses whether or not to fill
+    /// the rest of it's buffer with silence.
     pub fn pipe<Chan, Ch, S>(&mut self, audio: &Audio<Chan, CH>, mut sink: S)
     where
         Chan: Channel,
@@ -131,10 +468,16 @@ impl<const CH: usize> Stream<CH> {
         // Make sure that the sample rates match.
         assert_eq!(sink.sample_rate().get(), self.output_sample_rate);
 
-        // If sample rates match, do a copy (faster than resampling).
-        if self.channels[0].state.started == 0
+        // If sample rates match, do a copy (faster than resampling).  Once
+        // resampling has started, the filter holds the latency's worth of
+        // earlier audio, so it can't be bypassed (see `is_passthrough()`).  A
+        // fractional source rate (see `new_frac()`) that rounds to the target
+        // sample rate still needs resampling.
+        if self.is_passthrough()
             && sink.sample_rate() == audio.sample_rate()
+            && self.input_sample_rate != Some(audio.sample_rate())
         {
+            self.resampled_len += audio.len() as f64;
             sink.sink_with(&mut audio.iter().cloned().map(|x| x.to()));
             return;
         }
@@ -144,29 +487,262 @@ impl<const CH: usize> Stream<CH> {
         {
             self.source_hz(audio.sample_rate());
         }
+        self.resampled_len += audio.len() as f64 * f64::from(self.ratio.1)
+            / f64::from(self.ratio.0);
 
         // First, de-interleave input audio data into f32 buffer.
-        let converted = Audio::<Ch32, CH>::with_frames(
-            audio.sample_rate().get(),
-            audio
-                .as_slice()
+        self.deinterleave(audio.as_slice());
+
+        // Next, allocate space for output channels and resample.
+        self.resample_audio(sink);
+    }
+
+    /// Pipe audio through this stream like [`pipe()`](Self::pipe), but scale
+    /// the output so that its peak never exceeds the peak of `audio`.
+    ///
+    /// Resampling can overshoot the original peak near sharp transients,
+    /// which can clip once converted to an integer format.  The gain is
+    /// worked out separately for each call, so output with an overshoot is
+    /// slightly quieter than the rest (usually by well under 1 dB); pipe in
+    /// larger blocks to keep the level steadier.
+    pub fn pipe_peak_limited<Chan, Ch, S>(
+        &mut self,
+        audio: &Audio<Chan, CH>,
+        mut sink: S,
+    ) where
+        Chan: Channel,
+        Ch: Channel + From<Chan> + From<Ch32>,
+        S: Sink<Ch, CH>,
+        Ch32: From<Chan>,
+    {
+        let peak = |frames: &mut dyn Iterator<Item = f32>| {
+            frames.map(f32::abs).fold(0.0, f32::max)
+        };
+        let input_peak = peak(
+            &mut audio
+                .iter()
+                .flat_map(|frame| frame.channels().iter())
+                .map(|x| x.to_f32()),
+        );
+
+        let mut output = Vec::<Frame<Ch32, CH>>::new();
+        let hz = sink.sample_rate();
+        self.pipe(audio, VecSink::new(hz, sink.len(), &mut output));
+        let output_peak = peak(
+            &mut output
                 .iter()
-                .map(|frame| frame.to())
-                .collect::<Vec<_>>(),
+                .flat_map(|frame| frame.channels().iter())
+                .map(|x| x.to_f32()),
         );
+        let gain = if output_peak > input_peak {
+            input_peak / output_peak
+        } else {
+            1.0
+        };
+
+        sink.sink_with(&mut output.into_iter().map(|mut frame| {
+            frame.gain(gain);
+            for x in frame.channels_mut() {
+                // Rounding in the gain can't push a sample over the peak.
+                *x = Ch32::new(x.to_f32().max(-input_peak).min(input_peak));
+            }
+            frame.to()
+        }));
+    }
+
+    /// Pipe audio through this stream, and out to the sink, while gliding the
+    /// source sample rate linearly from `from_hz` to `to_hz` (ignoring the
+    /// sample rate of `audio`).
+    ///
+    /// Playing audio back at a higher source sample rate raises its pitch, so
+    /// this can be used for pitch glide and tape stop effects.  The rate is
+    /// updated every 256 frames, and is kept at 1 Hz or above, so a tape stop
+    /// can glide to a `to_hz` of 0.
+    pub fn pipe_glide<Chan, Ch, S>(
+        &mut self,
+        audio: &Audio<Chan, CH>,
+        from_hz: u32,
+        to_hz: u32,
+        mut sink: S,
+    ) where
+        Chan: Channel,
+        Ch: Channel + From<Chan>,
+        S: Sink<Ch, CH>,
+        Ch32: From<Chan>,
+    {
+        const BLOCK: usize = 256;
+
+        // Make sure that the sample rates match.
+        assert_eq!(sink.sample_rate().get(), self.output_sample_rate);
+
+        let (from_hz, to_hz) = (f64::from(from_hz), f64::from(to_hz));
+        let len = audio.len() as f64;
+        for (i, block) in audio.as_slice().chunks(BLOCK).enumerate() {
+            // Source sample rate at the center of the block.
+            let t = ((i * BLOCK) as f64 + block.len() as f64 / 2.0) / len;
+            let hz = (from_hz + (to_hz - from_hz) * t).round() as u32;
+            let hz = NonZeroU32::new(hz.max(1)).unwrap();
+            if Some(hz) != self.input_sample_rate {
+                self.source_hz(hz);
+            }
+            self.resampled_len += block.len() as f64
+                * f64::from(self.output_sample_rate)
+                / f64::from(hz.get());
+
+            self.deinterleave(block);
+            self.resample_audio(SinkRef(&mut sink));
+        }
+    }
+
+    /// Resample `input` into `output`, returning the number of frames
+    /// `(consumed, produced)`.
+    ///
+    /// Unlike [`pipe()`](Self::pipe), input is never dropped when `output`
+    /// fills up: frames after the first `consumed` weren't used, and should
+    /// be passed in again with the next call.  This suits streaming in fixed
+    /// size blocks.  The source sample rate is the one from
+    /// [`with_rates()`](Self::with_rates) or the last piped audio; until it is
+    /// known, frames are copied straight through.
+    pub fn process(
+        &mut self,
+        input: &[Frame<Ch32, CH>],
+        output: &mut [Frame<Ch32, CH>],
+    ) -> (usize, usize) {
+        if self.input_sample_rate.is_none() {
+            let len = input.len().min(output.len());
+            output[..len].copy_from_slice(&input[..len]);
+            self.resampled_len += len as f64;
+            return (len, len);
+        }
+        let (num, den) = self.ratio;
+
+        // The channel with the longest filter needs the most input to fill the
+        // output, so it decides how much input is consumed.  The other
+        // channels get the same input, and keep any samples that are ahead.
+        let longest = (0..CH)
+            .max_by_key(|&chan| self.channels[chan].state.filt_len)
+            .unwrap_or(0);
+        let mut consumed = 0;
+        if !input.is_empty() {
+            self.deinterleave(input);
+            let ch = &mut self.channels[longest];
+            let end = output.len().max(ch.pending);
+            if ch.output.len() < end {
+                ch.output.resize(end, 0.0);
+            }
+            if ch.pending < end {
+                let (used, produced) = ch.state.process_float(
+                    ch.input.as_slice(),
+                    &mut ch.output[ch.pending..end],
+                    den,
+                );
+                ch.pending += produced;
+                consumed = used;
+            }
+        }
+        if consumed != 0 {
+            // Room for all of the output from the consumed input.
+            let room = (consumed as u64 * u64::from(den) / u64::from(num))
+                as usize
+                + self.input_latency as usize
+                + 1;
+            for (chan, ch) in self.channels.iter_mut().enumerate() {
+                if chan == longest {
+                    continue;
+                }
+                let end = ch.pending + room;
+                if ch.output.len() < end {
+                    ch.output.resize(end, 0.0);
+                }
+                let (_, produced) = ch.state.process_float(
+                    &ch.input[..consumed],
+                    &mut ch.output[ch.pending..end],
+                    den,
+                );
+                ch.pending += produced;
+            }
+        }
+
+        // Re-interleave the samples that all channels have produced.
+        let produced = self
+            .channels
+            .iter()
+            .map(|ch| ch.pending)
+            .min()
+            .unwrap_or(0)
+            .min(output.len());
+        for (i, frame) in output[..produced].iter_mut().enumerate() {
+            for chan in 0..CH {
+                frame.channels_mut()[chan] =
+                    Ch32::new(self.channels[chan].output[i]);
+            }
+        }
+        for ch in self.channels.iter_mut() {
+            ch.output.copy_within(produced..ch.pending, 0);
+            ch.pending -= produced;
+        }
+
+        self.resampled_len += consumed as f64 * f64::from(den) / f64::from(num);
+        (consumed, produced)
+    }
+
+    /// Resample a single frame, returning the output frames it produced (as
+    /// many as the ratio between sample rates allows, or none while the
+    /// resampler's latency fills up).
+    ///
+    /// This suits event-driven audio, where input arrives a frame at a time.
+    /// Like [`process()`](Self::process), the source sample rate must already
+    /// be known; until it is, frames are copied straight through.  The output
+    /// buffer is reused, so after the first few calls this doesn't allocate.
+    pub fn push_frame<Chan, Ch>(
+        &mut self,
+        frame: Frame<Chan, CH>,
+    ) -> impl Iterator<Item = Frame<Ch, CH>> + '_
+    where
+        Chan: Channel,
+        Ch32: From<Chan>,
+        Ch: Channel + From<Ch32>,
+    {
+        let (num, den) = self.ratio;
+        // Room for all of the output from one frame of input, plus one.
+        let room = (den / num.max(1)) as usize + 2;
+        let frame = [frame.to::<Ch32, CH>()];
+        let mut input = &frame[..];
+        let mut output = mem::take(&mut self.frame_output);
+        let mut len = 0;
+        loop {
+            if output.len() < len + room {
+                output.resize(len + room, Frame::default());
+            }
+            let (consumed, produced) =
+                self.process(input, &mut output[len..len + room]);
+            input = &input[consumed..];
+            len += produced;
+            if produced == 0 && (consumed != 0 || input.is_empty()) {
+                break;
+            }
+        }
+        self.frame_output = output;
+        self.frame_output[..len].iter().map(|frame| frame.to())
+    }
+
+    /// De-interleave frames into the input buffer of each channel.
+    fn deinterleave<Chan>(&mut self, frames: &[Frame<Chan, CH>])
+    where
+        Chan: Channel,
+        Ch32: From<Chan>,
+    {
         for chan in 0..CH {
             self.channels[chan].input.clear();
         }
-        for frame in converted.iter() {
+        for frame in frames {
+            let frame: Frame<Ch32, CH> = frame.to();
             for chan in 0..CH {
                 self.channels[chan]
                     .input
                     .push(frame.channels()[chan].to_f32());
             }
         }
-
-        // Next, allocate space for output channels and resample.
-        self.resample_audio(sink);
     }
 
     fn resample_audio<Ch, S>(&mut self, mut sink: S)
@@ -179,24 +755,39 @@ impl<const CH: usize> Stream<CH> {
             return;
         }
 
-        let mut out = u32::MAX;
+        let mut out = usize::MAX;
+        let len = sink.len();
+        // Room for all of the output from the input, so that none of it is
+        // dropped when the predicted output length is off by a frame or the
+        // sink is short; frames that don't fit in the sink stay pending (up
+        // to a limit, see below).
+        let (num, den) = self.ratio;
+        let room = (self.channels[0].input.len() as u64 * u64::from(den)
+            / u64::from(num)) as usize
+            + self.input_latency as usize
+            + 1;
 
         // Allocate space for output channels and resample
-        for chan in 0..CH {
-            self.channels[chan].output.resize(sink.len(), 0.0);
-
-            // FIXME: Remove length parameters, return number of output samples.
-            self.channels[chan].state.process_float(
-                self.channels[chan].input.as_slice(),
-                &mut (self.channels[chan].input.len() as u32),
-                self.channels[chan].output.as_mut_slice(),
-                &mut out,
+        for ch in self.channels.iter_mut() {
+            // Only ever grow the output buffer, so that varying sink lengths
+            // don't cause reallocation.
+            let end = ch.pending + len.max(room);
+            if ch.output.len() < end {
+                ch.output.resize(end, 0.0);
+            }
+
+            let (_, produced) = ch.state.process_float(
+                ch.input.as_slice(),
+                &mut ch.output[ch.pending..end],
                 self.ratio.1,
             );
+            ch.pending += produced;
+            out = out.min(ch.pending);
         }
+        let out = out.min(len);
 
         // Then, re-interleave the samples back.
-        sink.sink_with(&mut (0..out as usize).into_iter().map(|i| {
+        sink.sink_with(&mut (0..out).into_iter().map(|i| {
             let mut out_frame = Frame::<Ch, CH>::default();
             for chan in 0..CH {
                 out_frame.channels_mut()[chan] =
@@ -204,6 +795,245 @@ impl<const CH: usize> Stream<CH> {
             }
             out_frame
         }));
+
+        // Keep samples that didn't fit in the sink, and samples from channels
+        // with a shorter filter (and so less latency) that are ahead of the
+        // rest.
+        for ch in self.channels.iter_mut() {
+            ch.output.copy_within(out..ch.pending, 0);
+            ch.pending -= out;
+        }
+
+        // Only carry over enough to make up for rounding, so a sink that's
+        // always short doesn't build up latency; the rest is dropped.  Every
+        // channel drops the same number of frames to stay lined up.
+        let limit = self.output_latency().max(1) as usize;
+        let kept = self.channels.iter().map(|ch| ch.pending).min();
+        let excess = kept.unwrap_or(0).saturating_sub(limit);
+        for ch in self.channels.iter_mut() {
+            ch.pending -= excess;
+        }
+    }
+}
+
+/// Resampling writer that owns its [`Sink`](crate::Sink), and automatically
+/// flushes the stream into it when dropped.
+///
+/// This guarantees the final frames buffered in the resampler are never
+/// forgotten, which would truncate the output.
+#[derive(Debug)]
+pub struct ResampleWriter<Chan, S, const CH: usize>
+where
+    Chan: Channel,
+    S: Sink<Chan, CH>,
+{
+    stream: Stream<CH>,
+    sink: S,
+    _phantom: PhantomData<Chan>,
+}
+
+impl<Chan, S, const CH: usize> ResampleWriter<Chan, S, CH>
+where
+    Chan: Channel,
+    S: Sink<Chan, CH>,
+{
+    /// Create a new writer, resampling to the sample rate of `sink`.
+    pub fn new(sink: S) -> Self {
+        Self {
+            stream: Stream::new(sink.sample_rate().get()),
+            sink,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Resample audio and write it to the sink.
+    pub fn write<C>(&mut self, audio: &Audio<C, CH>)
+    where
+        C: Channel,
+        Chan: From<C>,
+        Ch32: From<C>,
+    {
+        self.stream.pipe(audio, SinkRef(&mut self.sink));
+    }
+}
+
+impl<Chan, S, const CH: usize> Drop for ResampleWriter<Chan, S, CH>
+where
+    Chan: Channel,
+    S: Sink<Chan, CH>,
+{
+    fn drop(&mut self) {
+        self.stream.drain(SinkRef(&mut self.sink));
+    }
+}
+
+/// Resampler that owns both a [`Stream`] and the [`Sink`](crate::Sink) it
+/// outputs to, created with [`Stream::into_transcoder()`].
+#[derive(Debug)]
+pub struct Transcoder<Chan, S, const CH: usize>
+where
+    Chan: Channel,
+    S: Sink<Chan, CH>,
+{
+    stream: Stream<CH>,
+    sink: S,
+    _phantom: PhantomData<Chan>,
+}
+
+impl<Chan, S, const CH: usize> Transcoder<Chan, S, CH>
+where
+    Chan: Channel,
+    S: Sink<Chan, CH>,
+{
+    /// Resample audio and push it to the sink.
+    pub fn push<C>(&mut self, audio: &Audio<C, CH>)
+    where
+        C: Channel,
+        Chan: From<C>,
+        Ch32: From<C>,
+    {
+        self.stream.pipe(audio, SinkRef(&mut self.sink));
+    }
+
+    /// Flush the stream into the sink, and return the sink.
+    pub fn finish(mut self) -> S {
+        self.stream.drain(SinkRef(&mut self.sink));
+        self.sink
+    }
+}
+
+/// Number of frames pulled from the source iterator of [`resample_iter()`] at
+/// a time.
+const RESAMPLE_ITER_CHUNK: usize = 1024;
+
+/// Resample the frames of any iterator from `src_hz` to `dst_hz`, lazily.
+///
+/// Source frames are pulled in blocks as the output is consumed, and the
+/// stream is flushed once the source runs out, so the output has the full
+/// [resampled length](Stream::resampled_len).
+pub fn resample_iter<Chan, I, const CH: usize>(
+    src_hz: u32,
+    dst_hz: u32,
+    frames: I,
+) -> impl Iterator<Item = Frame<Chan, CH>>
+where
+    Chan: Channel,
+    I: Iterator<Item = Frame<Chan, CH>>,
+    Ch32: From<Chan>,
+{
+    ResampleIter {
+        stream: Some(Stream::new(dst_hz)),
+        src_hz,
+        dst_hz: dst_hz.try_into().unwrap(),
+        frames,
+        output: Vec::new(),
+        index: 0,
+        emitted: 0,
+    }
+}
+
+/// Resample interleaved `f32` samples with `channels` channels from `src_hz`
+/// to `dst_hz`, returning interleaved `f32` samples.
+///
+/// This is the same as resampling an `Audio<Ch32, CH>` with a [`Stream`], and
+/// flushing it, without having to pick the channel count at compile time.
+///
+/// # Panics
+/// If `channels` isn't from 1 to 8 or 12, `input` isn't made up of whole
+/// frames, or either sample rate is zero.
+pub fn resample_f32_interleaved(
+    channels: usize,
+    src_hz: u32,
+    dst_hz: u32,
+    input: &[f32],
+) -> Vec<f32> {
+    match channels {
+        1 => resample_f32::<1>(src_hz, dst_hz, input),
+        2 => resample_f32::<2>(src_hz, dst_hz, input),
+        3 => resample_f32::<3>(src_hz, dst_hz, input),
+        4 => resample_f32::<4>(src_hz, dst_hz, input),
+        5 => resample_f32::<5>(src_hz, dst_hz, input),
+        6 => resample_f32::<6>(src_hz, dst_hz, input),
+        7 => resample_f32::<7>(src_hz, dst_hz, input),
+        8 => resample_f32::<8>(src_hz, dst_hz, input),
+        12 => resample_f32::<12>(src_hz, dst_hz, input),
+        _ => panic!("Unsupported channel count: {}", channels),
+    }
+}
+
+/// Resample interleaved `f32` samples with `CH` channels.
+fn resample_f32<const CH: usize>(
+    src_hz: u32,
+    dst_hz: u32,
+    input: &[f32],
+) -> Vec<f32> {
+    let audio = Audio::<Ch32, CH>::with_f32_buffer(src_hz, input);
+    let frames = resample_iter(src_hz, dst_hz, audio.iter().cloned());
+    frames
+        .flat_map(|frame| {
+            let mut samples = [0.0; CH];
+            for (sample, chan) in samples.iter_mut().zip(frame.channels()) {
+                *sample = chan.to_f32();
+            }
+            samples
+        })
+        .collect()
+}
+
+/// Iterator returned by [`resample_iter()`].
+struct ResampleIter<Chan: Channel, I, const CH: usize> {
+    // Taken when the source runs out and the stream is flushed.
+    stream: Option<Stream<CH>>,
+    src_hz: u32,
+    dst_hz: NonZeroU32,
+    frames: I,
+    // Resampled frames, and the index of the next one to yield.
+    output: Vec<Frame<Chan, CH>>,
+    index: usize,
+    // Total number of frames resampled into `output` so far.
+    emitted: usize,
+}
+
+impl<Chan, I, const CH: usize> Iterator for ResampleIter<Chan, I, CH>
+where
+    Chan: Channel,
+    I: Iterator<Item = Frame<Chan, CH>>,
+    Ch32: From<Chan>,
+{
+    type Item = Frame<Chan, CH>;
+
+    fn next(&mut self) -> Option<Frame<Chan, CH>> {
+        loop {
+            if let Some(frame) = self.output.get(self.index) {
+                self.index += 1;
+                return Some(*frame);
+            }
+            self.output.clear();
+            self.index = 0;
+
+            let stream = self.stream.as_mut()?;
+            let chunk = self
+                .frames
+                .by_ref()
+                .take(RESAMPLE_ITER_CHUNK)
+                .collect::<Vec<_>>();
+            if chunk.is_empty() {
+                let room = stream.resampled_len().saturating_sub(self.emitted);
+                let sink = VecSink::new(self.dst_hz, room, &mut self.output);
+                self.stream.take().unwrap().flush(sink);
+            } else {
+                // Upper bound on the number of frames the resampler can
+                // produce.
+                let room = (chunk.len() as u64 * u64::from(self.dst_hz.get()))
+                    / u64::from(self.src_hz)
+                    + 2;
+                let audio = Audio::with_frames(self.src_hz, chunk);
+                let sink =
+                    VecSink::new(self.dst_hz, room as usize, &mut self.output);
+                stream.pipe(&audio, sink);
+            }
+            self.emitted += self.output.len();
+        }
     }
 }
 
@@ -216,9 +1046,23 @@ struct Resampler32 {
     input: Vec<f32>,
     // De-interleaved output audio stream for a single channel.
     output: Vec<f32>,
+    // Number of samples at the start of `output` not yet sent to the sink.
+    pending: usize,
+}
+
+/// Pick a resampling quality for the ratio between sample rates (see
+/// `Stream::new_auto()`).
+fn auto_quality(src_hz: u32, dst_hz: u32) -> usize {
+    let ratio = f64::from(src_hz) / f64::from(dst_hz);
+    if ratio >= 2.0 {
+        speex::MAX_QUALITY
+    } else if (ratio - 1.0).abs() <= 0.1 {
+        5
+    } else {
+        speex::DEFAULT_QUALITY
+    }
 }
 
-/// Simplify a ratio (fraction with non-zero numerator and denominator).
 #[inline(always)]
 fn simplify(num: u32, den: u32) -> (u32, u32) {
     debug_assert_ne!(num, 0);
@@ -240,3 +1084,619 @@ fn gcd(mut a: u32, mut b: u32) -> u32 {
     }
     b
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sink::VecSink;
+    use alloc::sync::Arc;
+
+    #[test]
+    fn with_rates_latency() {
+        let stream = Stream::<2>::with_rates(44_100, 48_000);
+        assert_eq!(stream.input_latency(), 80);
+        assert_eq!(stream.output_latency(), 87);
+
+        // Same latency as learning the source sample rate while piping.
+        let audio = Audio::<Ch32, 2>::with_silence(44_100, 441);
+        let mut output = Audio::<Ch32, 2>::with_silence(48_000, 480);
+        let mut piped = Stream::<2>::new(48_000);
+        assert_eq!(piped.input_latency(), 0);
+        piped.pipe(&audio, output.sink());
+        assert_eq!(piped.input_latency(), stream.input_latency());
+        assert_eq!(piped.output_latency(), stream.output_latency());
+
+        let stream = Stream::<2>::with_rates(48_000, 48_000);
+        assert_eq!(stream.output_latency(), 0);
+    }
+
+    #[test]
+    fn flush_exact_len() {
+        let frames = (0..1000)
+            .map(|i| Frame::<Ch32, 1>::new(Ch32::new((i as f32 * 0.1).sin())))
+            .collect::<Vec<_>>();
+
+        for (src, dst) in [(44_100, 48_000), (48_000, 44_100), (48_000, 8_000)]
+        {
+            for chunk in [1000, 137] {
+                let hz = NonZeroU32::new(dst).unwrap();
+                let mut output = Vec::<Frame<Ch32, 1>>::new();
+                let mut stream = Stream::new(dst);
+                for frames in frames.chunks(chunk) {
+                    let audio = Audio::with_frames(src, frames);
+                    stream.pipe(&audio, VecSink::new(hz, 4096, &mut output));
+                }
+                let len = stream.resampled_len();
+                assert_eq!(
+                    len,
+                    (1000 * dst as usize + src as usize - 1) / src as usize
+                );
+                stream.flush(VecSink::new(hz, 4096, &mut output));
+
+                assert_eq!(output.len(), len);
+                // The end of the input isn't lost.
+                let tail = output[len - 8..]
+                    .iter()
+                    .map(|f| f.channels()[0].to_f32().abs())
+                    .fold(0.0, f32::max);
+                assert!(tail > 0.1);
+            }
+        }
+    }
+
+    #[test]
+    fn pipe_glide() {
+        let hz = 48_000;
+        let tone = (0..hz / 2)
+            .map(|i| {
+                let x = i as f32 * 500.0 / hz as f32;
+                Frame::<Ch32, 1>::new(Ch32::new(
+                    (x * 2.0 * core::f32::consts::PI).sin(),
+                ))
+            })
+            .collect::<Vec<_>>();
+        let tone = Audio::with_frames(hz, tone);
+
+        for (to_hz, end_pitch) in [(96_000, 1000.0), (24_000, 250.0)] {
+            let mut output = Vec::new();
+            let mut stream = Stream::new(hz);
+            let sink = VecSink::new(
+                NonZeroU32::new(hz).unwrap(),
+                hz as usize * 2,
+                &mut output,
+            );
+            stream.pipe_glide(&tone, hz, to_hz, sink);
+
+            let pitch = |frames: &[Frame<Ch32, 1>]| {
+                Audio::with_frames(hz, frames).estimate_pitch().unwrap()
+            };
+            let start = pitch(&output[..1024]);
+            let end = pitch(&output[output.len() - 1024..]);
+            assert!((start - 500.0).abs() < 500.0 * 0.04);
+            assert!((end - end_pitch).abs() < end_pitch * 0.04);
+        }
+
+        // Gliding to 0 Hz stops at 1 Hz, instead of panicking.
+        let mut output = Vec::<Frame<Ch32, 1>>::new();
+        let mut stream = Stream::new(8);
+        let sink = VecSink::new(NonZeroU32::new(8).unwrap(), 4096, &mut output);
+        stream.pipe_glide(
+            &Audio::with_frames(hz, &tone.as_slice()[..256]),
+            0,
+            0,
+            sink,
+        );
+        assert_eq!(stream.resampled_len(), 256 * 8);
+    }
+
+    #[test]
+    fn twelve_channels() {
+        let frames = (0..4410)
+            .map(|i| Frame::<Ch32, 12>::from((i as f32 * 0.05).sin() * 0.5))
+            .collect::<Vec<_>>();
+        let audio = Audio::with_frames(44_100, frames);
+        let hz = NonZeroU32::new(48_000).unwrap();
+        let mut stream = Stream::<12>::new(48_000);
+        let mut output = Vec::<Frame<Ch32, 12>>::new();
+        stream.pipe(&audio, VecSink::new(hz, 8192, &mut output));
+        let len = stream.resampled_len();
+        stream.flush(VecSink::new(hz, 8192, &mut output));
+        assert_eq!(output.len(), len);
+        assert_eq!(len, 4800);
+        for frame in &output {
+            let first = frame.channels()[0];
+            assert!(frame.channels().iter().all(|chan| *chan == first));
+        }
+
+        // Also when mixing down while resampling.
+        let resampled = Audio::<Ch32, 12>::with_audio(48_000, &audio);
+        assert_eq!(resampled.as_slice(), output.as_slice());
+        let stereo = Audio::<Ch32, 2>::with_audio(48_000, &audio);
+        assert_eq!(stereo.len(), 4800);
+    }
+
+    #[test]
+    fn channel_quality() {
+        let mut stream = Stream::<6>::with_rates(44_100, 48_000);
+        stream.set_channel_quality(3, 0);
+        let lfe = stream.channels[3].state.filt_len;
+        for chan in [0, 1, 2, 4, 5] {
+            assert!(lfe < stream.channels[chan].state.filt_len);
+        }
+        assert_eq!(stream.input_latency(), 80);
+
+        // A low tone comes out of the LFE channel in line with the others.
+        let frames = (0..4410)
+            .map(|i| {
+                let x = i as f32 * 100.0 / 44_100.0;
+                let s = Ch32::new((x * 2.0 * core::f32::consts::PI).sin());
+                Frame::<Ch32, 6>::new(s, s, s, s, s, s)
+            })
+            .collect::<Vec<_>>();
+        let hz = NonZeroU32::new(48_000).unwrap();
+        let mut output = Vec::<Frame<Ch32, 6>>::new();
+        for frames in frames.chunks(1000) {
+            let audio = Audio::with_frames(44_100, frames);
+            stream.pipe(&audio, VecSink::new(hz, 4096, &mut output));
+        }
+        let len = stream.resampled_len();
+        stream.flush(VecSink::new(hz, 4096, &mut output));
+        assert_eq!(output.len(), len);
+        for frame in &output[100..len - 100] {
+            let front = frame.channels()[0].to_f32();
+            let lfe = frame.channels()[3].to_f32();
+            assert!((front - lfe).abs() < 0.02);
+        }
+    }
+
+    #[test]
+    fn window_aliasing() {
+        // A 12 kHz tone is above Nyquist at 16 kHz, so anything left is
+        // aliasing.
+        let frames = (0..9600)
+            .map(|i| {
+                let x = i as f32 * 12_000.0 / 48_000.0;
+                let s = (x * 2.0 * core::f32::consts::PI).sin();
+                Frame::<Ch32, 1>::new(Ch32::new(s))
+            })
+            .collect::<Vec<_>>();
+        let audio = Audio::with_frames(48_000, frames);
+        let hz = NonZeroU32::new(16_000).unwrap();
+
+        let aliasing = |window| {
+            let mut output = Vec::<Frame<Ch32, 1>>::new();
+            let mut stream = Stream::with_rates(48_000, 16_000);
+            stream.set_window(window);
+            stream.pipe(&audio, VecSink::new(hz, 4096, &mut output));
+            output
+                .iter()
+                .map(|f| f.channels()[0].to_f32().powi(2))
+                .sum::<f32>()
+        };
+        let kaiser6 = aliasing(Window::Kaiser6);
+        let kaiser8 = aliasing(Window::Kaiser8);
+        let kaiser10 = aliasing(Window::Kaiser10);
+        assert!(kaiser8 < kaiser6, "{} {}", kaiser8, kaiser6);
+        assert!(kaiser10 < kaiser8, "{} {}", kaiser10, kaiser8);
+    }
+
+    #[test]
+    fn passthrough() {
+        let frames = (0..480)
+            .map(|i| Frame::<Ch32, 1>::new(Ch32::new((i as f32 * 0.1).sin())))
+            .collect::<Vec<_>>();
+        let audio = Audio::with_frames(48_000, frames);
+        let hz = NonZeroU32::new(48_000).unwrap();
+
+        for mut stream in
+            [Stream::new(48_000), Stream::with_rates(48_000, 48_000)]
+        {
+            let mut output = Vec::new();
+            for _ in 0..4 {
+                stream.pipe(&audio, VecSink::new(hz, 480, &mut output));
+                assert!(stream.is_passthrough());
+            }
+            assert_eq!(stream.channels[0].state.started, 0);
+            assert_eq!(&output[1440..], audio.as_slice());
+        }
+
+        let mut stream = Stream::new(48_000);
+        let other = Audio::<Ch32, 1>::with_silence(44_100, 441);
+        stream.pipe(
+            &other,
+            VecSink::new(hz, 480, &mut Vec::<Frame<Ch32, 1>>::new()),
+        );
+        assert!(!stream.is_passthrough());
+    }
+
+    #[test]
+    fn short_sink() {
+        // 100 frames at 48 kHz is 91.875 frames at 44.1 kHz, so a sink sized
+        // for the rounded down length is a frame short every time.
+        let frames = (0..1000)
+            .map(|i| Frame::<Ch32, 2>::from((i as f32 * 0.05).sin()))
+            .collect::<Vec<_>>();
+        let hz = NonZeroU32::new(44_100).unwrap();
+
+        let mut expected = Vec::<Frame<Ch32, 2>>::new();
+        let mut stream = Stream::new(44_100);
+        for frames in frames.chunks(100) {
+            let audio = Audio::with_frames(48_000, frames);
+            stream.pipe(&audio, VecSink::new(hz, 4096, &mut expected));
+        }
+        stream.flush(VecSink::new(hz, 4096, &mut expected));
+
+        let mut output = Vec::<Frame<Ch32, 2>>::new();
+        let mut stream = Stream::new(44_100);
+        for frames in frames.chunks(100) {
+            let audio = Audio::with_frames(48_000, frames);
+            stream.pipe(&audio, VecSink::new(hz, 91, &mut output));
+        }
+        let len = stream.resampled_len();
+        stream.flush(VecSink::new(hz, 4096, &mut output));
+        assert_eq!(len, 919);
+        assert_eq!(output.len(), len);
+        assert_eq!(output, expected);
+
+        // Even an empty sink doesn't lose audio.
+        let mut output = Vec::<Frame<Ch32, 2>>::new();
+        let mut stream = Stream::new(44_100);
+        let audio = Audio::with_frames(48_000, &frames[..1]);
+        stream.pipe(&audio, VecSink::new(hz, 0, &mut output));
+        stream.flush(VecSink::new(hz, 4096, &mut output));
+        assert_eq!(output.len(), 1);
+
+        // A sink that's always too short doesn't build up pending frames.
+        let audio = Audio::<Ch32, 2>::with_silence(44_100, 1000);
+        let mut output = Audio::<Ch32, 2>::with_silence(48_000, 1000);
+        let mut stream = Stream::new(48_000);
+        for _ in 0..50 {
+            stream.pipe(&audio, output.sink());
+            let limit = stream.output_latency() as usize;
+            // Room for one pipe's output, plus what's carried over.
+            let room = 1089 + stream.input_latency() as usize + limit;
+            for ch in stream.channels.iter() {
+                assert!(ch.pending <= limit);
+                assert!(ch.output.len() <= room);
+            }
+        }
+    }
+
+    #[test]
+    fn output_buffer_grows() {
+        let audio = Audio::<Ch32, 2>::with_silence(44_100, 441);
+        let hz = NonZeroU32::new(48_000).unwrap();
+        let mut stream = Stream::new(48_000);
+        let mut output = Vec::<Frame<Ch32, 2>>::new();
+        stream.pipe(&audio, VecSink::new(hz, 2048, &mut output));
+        let ptr = stream.channels[0].output.as_ptr();
+        for len in [512, 2048, 600, 1024, 512] {
+            stream.pipe(&audio, VecSink::new(hz, len, &mut output));
+            assert_eq!(stream.channels[0].output.len(), 2048);
+            assert_eq!(stream.channels[0].output.as_ptr(), ptr);
+        }
+    }
+
+    #[test]
+    fn transcoder() {
+        let frames = (0..1000)
+            .map(|i| Frame::<Ch32, 1>::new(Ch32::new((i as f32 * 0.1).sin())))
+            .collect::<Vec<_>>();
+        let hz = NonZeroU32::new(48_000).unwrap();
+
+        let mut expected = Vec::<Frame<Ch32, 1>>::new();
+        let mut stream = Stream::new(48_000);
+        let audio = Audio::with_frames(44_100, frames.as_slice());
+        stream.pipe(&audio, VecSink::new(hz, 4096, &mut expected));
+        let len = stream.resampled_len();
+        stream.flush(VecSink::new(hz, 4096, &mut expected));
+        assert_eq!(expected.len(), len);
+
+        let mut output = Vec::new();
+        let sink = VecSink::new(hz, 4096, &mut output);
+        let mut transcoder = Stream::new(48_000).into_transcoder(sink);
+        for frames in frames.chunks(300) {
+            transcoder.push(&Audio::with_frames(44_100, frames));
+        }
+        let sink = transcoder.finish();
+        assert_eq!(sink.len(), 4096 - len);
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn resample_iter() {
+        let frames = (0..3000)
+            .map(|i| Frame::<Ch32, 1>::new(Ch32::new((i as f32 * 0.1).sin())))
+            .collect::<Vec<_>>();
+        let hz = NonZeroU32::new(48_000).unwrap();
+
+        let mut expected = Vec::<Frame<Ch32, 1>>::new();
+        let mut stream = Stream::new(48_000);
+        let audio = Audio::with_frames(44_100, frames.as_slice());
+        stream.pipe(&audio, VecSink::new(hz, 4096, &mut expected));
+        stream.flush(VecSink::new(hz, 4096, &mut expected));
+
+        let output =
+            super::resample_iter(44_100, 48_000, frames.iter().cloned())
+                .collect::<Vec<_>>();
+        assert_eq!(output, expected);
+
+        // Same rate is copied straight through.
+        let output =
+            super::resample_iter(44_100, 44_100, frames.iter().cloned())
+                .collect::<Vec<_>>();
+        assert_eq!(output, frames);
+    }
+
+    #[test]
+    fn resample_f32_interleaved() {
+        // 48 kHz to 16 kHz mono.
+        let input = (0..4800)
+            .map(|i| (i as f32 * 0.01).sin())
+            .collect::<Vec<_>>();
+        let output = super::resample_f32_interleaved(1, 48_000, 16_000, &input);
+        let hz = NonZeroU32::new(16_000).unwrap();
+        let mut expected = Vec::<Frame<Ch32, 1>>::new();
+        let mut stream = Stream::new(16_000);
+        let audio = Audio::<Ch32, 1>::with_f32_buffer(48_000, input);
+        stream.pipe(&audio, VecSink::new(hz, 4096, &mut expected));
+        stream.flush(VecSink::new(hz, 4096, &mut expected));
+        assert_eq!(output.len(), 1600);
+        assert_eq!(Audio::with_frames(16_000, expected).as_f32_slice(), output);
+
+        // Stereo, interleaved the same way.
+        let input = (0..2000)
+            .map(|i| {
+                (i as f32 * 0.05).sin() * if i % 2 == 0 { 1.0 } else { -0.5 }
+            })
+            .collect::<Vec<_>>();
+        let output = super::resample_f32_interleaved(2, 44_100, 48_000, &input);
+        let hz = NonZeroU32::new(48_000).unwrap();
+        let mut expected = Vec::<Frame<Ch32, 2>>::new();
+        let mut stream = Stream::new(48_000);
+        let audio = Audio::<Ch32, 2>::with_f32_buffer(44_100, input);
+        stream.pipe(&audio, VecSink::new(hz, 4096, &mut expected));
+        stream.flush(VecSink::new(hz, 4096, &mut expected));
+        assert_eq!(Audio::with_frames(48_000, expected).as_f32_slice(), output);
+
+        // 12 channels (7.1.4).
+        let input = [0.25; 12 * 441];
+        let output =
+            super::resample_f32_interleaved(12, 44_100, 48_000, &input);
+        assert_eq!(output.len(), 12 * 480);
+    }
+
+    #[test]
+    fn writer_flushes_on_drop() {
+        let frames = (0..480)
+            .map(|i| Frame::<Ch32, 1>::new(Ch32::new((i as f32 * 0.1).sin())))
+            .collect::<Vec<_>>();
+        let audio = Audio::with_frames(44_100, frames);
+
+        let mut expected = Audio::<Ch32, 1>::with_silence(48_000, 600);
+        let mut sink = expected.sink();
+        let mut stream = Stream::new(48_000);
+        stream.pipe(&audio, &mut sink);
+        stream.flush(&mut sink);
+
+        let mut output = Audio::<Ch32, 1>::with_silence(48_000, 600);
+        {
+            let mut writer = ResampleWriter::new(output.sink());
+            writer.write(&audio);
+        }
+
+        assert_eq!(output.as_slice(), expected.as_slice());
+
+        // Without flushing, the output would be truncated.
+        let mut truncated = Audio::<Ch32, 1>::with_silence(48_000, 600);
+        Stream::new(48_000).pipe(&audio, &mut truncated.sink());
+        assert_ne!(truncated.as_slice(), expected.as_slice());
+    }
+
+    #[test]
+    fn process_counts() {
+        let frames = (0..2000)
+            .map(|i| {
+                let s = Ch32::new((i as f32 * 0.05).sin());
+                Frame::<Ch32, 2>::new(s, -s)
+            })
+            .collect::<Vec<_>>();
+
+        // Pipe everything at once for reference (with a shorter filter on
+        // one channel, so the channels have different latencies).
+        let mut reference = Vec::<Frame<Ch32, 2>>::new();
+        let mut stream = Stream::<2>::with_rates(44_100, 48_000);
+        stream.set_channel_quality(1, 0);
+        let hz = NonZeroU32::new(48_000).unwrap();
+        let audio = Audio::with_frames(44_100, frames.as_slice());
+        stream.pipe(&audio, VecSink::new(hz, 4096, &mut reference));
+
+        // Then in small output blocks, passing unconsumed input back in.
+        let mut stream = Stream::<2>::with_rates(44_100, 48_000);
+        stream.set_channel_quality(1, 0);
+        let mut output = Vec::new();
+        let mut remaining = frames.as_slice();
+        let mut block = [Frame::<Ch32, 2>::default(); 100];
+        loop {
+            let (consumed, produced) = stream.process(remaining, &mut block);
+            assert!(produced <= block.len());
+            assert_eq!(consumed + remaining[consumed..].len(), remaining.len());
+            output.extend_from_slice(&block[..produced]);
+            remaining = &remaining[consumed..];
+            if consumed == 0 && produced == 0 {
+                break;
+            }
+        }
+        assert!(remaining.is_empty());
+        assert_eq!(output, reference);
+        assert_eq!(stream.resampled_len(), (2000 * 48_000 + 44_099) / 44_100);
+
+        // Same sample rate is copied.
+        let mut stream = Stream::<2>::with_rates(48_000, 48_000);
+        let mut block = [Frame::<Ch32, 2>::default(); 100];
+        assert_eq!(stream.process(&frames, &mut block), (100, 100));
+        assert_eq!(block[..], frames[..100]);
+    }
+
+    #[test]
+    fn push_frame() {
+        let frames = (0..2000)
+            .map(|i| {
+                let s = Ch32::new((i as f32 * 0.05).sin());
+                Frame::<Ch32, 2>::new(s, -s)
+            })
+            .collect::<Vec<_>>();
+
+        for (src, dst) in [(44_100, 48_000), (48_000, 44_100), (8_000, 48_000)]
+        {
+            let hz = NonZeroU32::new(dst).unwrap();
+            let mut expected = Vec::<Frame<Ch32, 2>>::new();
+            let mut stream = Stream::<2>::with_rates(src, dst);
+            let audio = Audio::with_frames(src, frames.as_slice());
+            stream.pipe(&audio, VecSink::new(hz, 20_000, &mut expected));
+            stream.flush(VecSink::new(hz, 20_000, &mut expected));
+
+            let mut output = Vec::<Frame<Ch32, 2>>::new();
+            let mut stream = Stream::<2>::with_rates(src, dst);
+            for frame in frames.iter() {
+                let len = output.len();
+                output.extend(stream.push_frame::<Ch32, Ch32>(*frame));
+                assert!(output.len() - len <= (dst / src + 1) as usize);
+            }
+            stream.flush(VecSink::new(hz, 20_000, &mut output));
+            assert_eq!(output, expected);
+        }
+    }
+
+    #[test]
+    fn pipe_peak_limited() {
+        // A square wave, which overshoots when band limited.
+        let frames = (0..2000)
+            .map(|i| {
+                Frame::<Ch32, 1>::from(if i / 20 % 2 == 0 { 0.9 } else { -0.9 })
+            })
+            .collect::<Vec<_>>();
+        let audio = Audio::with_frames(44_100, frames);
+        let hz = NonZeroU32::new(48_000).unwrap();
+        let peak = |frames: &[Frame<Ch32, 1>]| {
+            frames
+                .iter()
+                .map(|frame| frame.channels()[0].to_f32().abs())
+                .fold(0.0, f32::max)
+        };
+
+        let mut output = Vec::<Frame<Ch32, 1>>::new();
+        let mut stream = Stream::<1>::new(48_000);
+        stream.pipe(&audio, VecSink::new(hz, 4096, &mut output));
+        assert!(peak(&output) > 0.95, "{}", peak(&output));
+
+        let mut limited = Vec::<Frame<Ch32, 1>>::new();
+        let mut stream = Stream::<1>::new(48_000);
+        stream.pipe_peak_limited(&audio, VecSink::new(hz, 4096, &mut limited));
+        assert_eq!(limited.len(), output.len());
+        assert!(peak(&limited) <= 0.9, "{}", peak(&limited));
+        assert!(peak(&limited) > 0.85, "{}", peak(&limited));
+    }
+
+    #[test]
+    fn shared_filter() {
+        let frames = (0..3000)
+            .map(|i| {
+                let s = Ch32::new((i as f32 * 0.03).sin());
+                Frame::<Ch32, 2>::new(s, s * Ch32::new(0.5))
+            })
+            .collect::<Vec<_>>();
+        let audio = Audio::with_frames(44_100, frames);
+        let hz = NonZeroU32::new(48_000).unwrap();
+        let resample = |mut stream: Stream<2>| {
+            let mut output = Vec::<Frame<Ch32, 2>>::new();
+            stream.pipe(&audio, VecSink::new(hz, 4096, &mut output));
+            stream.flush(VecSink::new(hz, 4096, &mut output));
+            output
+        };
+
+        let filter = ResamplerFilter::new(44_100, 48_000, 5);
+        let a = Stream::<2>::with_filter(&filter);
+        let b = Stream::<2>::with_filter(&filter);
+        assert!(Arc::ptr_eq(
+            &a.channels[0].state.sinc_table,
+            &b.channels[1].state.sinc_table
+        ));
+        assert_eq!(a.input_latency(), b.input_latency());
+
+        let mut independent = Stream::<2>::with_rates(44_100, 48_000);
+        independent.set_channel_quality(0, 5);
+        independent.set_channel_quality(1, 5);
+        assert_eq!(a.input_latency(), independent.input_latency());
+        let expected = resample(independent);
+        assert_eq!(resample(a), expected);
+        assert_eq!(resample(b), expected);
+    }
+
+    #[test]
+    fn auto_quality() {
+        let pipe = |stream: &mut Stream<2>, dst_hz| {
+            let audio = Audio::<Ch32, 2>::with_silence(48_000, 480);
+            let hz = NonZeroU32::new(dst_hz).unwrap();
+            let mut output = Vec::<Frame<Ch32, 2>>::new();
+            stream.pipe(&audio, VecSink::new(hz, 1024, &mut output));
+        };
+        let quality = |dst_hz| {
+            let mut stream = Stream::<2>::new_auto(dst_hz);
+            pipe(&mut stream, dst_hz);
+            stream.channels[1].state.quality
+        };
+        assert_eq!(quality(8_000), 10);
+        assert_eq!(quality(44_100), 5);
+        assert_eq!(quality(32_000), 8);
+        assert!(quality(8_000) > quality(44_100));
+
+        // Until set manually.
+        let mut stream = Stream::<2>::new_auto(8_000);
+        stream.set_channel_quality(0, 3);
+        pipe(&mut stream, 8_000);
+        assert_eq!(stream.channels[0].state.quality, 3);
+        assert_eq!(stream.channels[1].state.quality, 8);
+    }
+
+    #[test]
+    fn new_frac() {
+        let stream = Stream::<1>::new_frac(88_201, 2, 48_000);
+        assert_eq!(stream.ratio, (88_201, 96_000));
+        assert_eq!(stream.input_sample_rate, NonZeroU32::new(44_101));
+
+        // 2001 frames at 1000.5 Hz is exactly 4000 frames at 2 kHz, which
+        // rounding the source to 1000 or 1001 Hz would miss.
+        let frames = (0..2001)
+            .map(|i| Frame::<Ch32, 1>::new(Ch32::new((i as f32 * 0.1).sin())))
+            .collect::<Vec<_>>();
+        let hz = NonZeroU32::new(2_000).unwrap();
+        let len = |mut stream: Stream<1>, audio_hz| {
+            let mut output = Vec::<Frame<Ch32, 1>>::new();
+            let audio = Audio::with_frames(audio_hz, frames.as_slice());
+            stream.pipe(&audio, VecSink::new(hz, 10_000, &mut output));
+            stream.flush(VecSink::new(hz, 10_000, &mut output));
+            output.len()
+        };
+        assert_eq!(len(Stream::new_frac(2001, 2, 2_000), 1_001), 4_000);
+        assert_eq!(len(Stream::new(2_000), 1_001), 3_999);
+        assert_eq!(len(Stream::new(2_000), 1_000), 4_002);
+    }
+
+    #[test]
+    fn effective_rates() {
+        assert_eq!(Stream::<2>::new(48_000).effective_rates(), (0, 1));
+        let stream = Stream::<2>::with_rates(44_100, 48_000);
+        assert_eq!(stream.effective_rates(), (147, 160));
+        let stream = Stream::<2>::new_frac(88_201, 2, 48_000);
+        assert_eq!(stream.effective_rates(), (88_201, 96_000));
+
+        // Learned from the first audio piped in.
+        let mut stream = Stream::<1>::new(16_000);
+        let audio = Audio::<Ch32, 1>::with_silence(48_000, 480);
+        let hz = NonZeroU32::new(16_000).unwrap();
+        let mut output = Vec::<Frame<Ch32, 1>>::new();
+        stream.pipe(&audio, VecSink::new(hz, 1_000, &mut output));
+        assert_eq!(stream.effective_rates(), (3, 1));
+    }
+}
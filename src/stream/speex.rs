@@ -1,14 +1,16 @@
 // FIXME: Once remove macros, can delete
 #![allow(trivial_casts, trivial_numeric_casts)]
 
-#[cfg(not(test))]
-use crate::math::Libm;
+use_libm!();
 
+use alloc::sync::Arc;
 use alloc::vec;
 use alloc::vec::Vec;
 use core::f64::consts::PI;
 use core::mem;
 
+use super::Window;
+
 #[derive(Clone)]
 pub(crate) struct ResamplerState {
     pub(crate) filt_len: u32,
@@ -20,7 +22,9 @@ pub(crate) struct ResamplerState {
     pub(crate) oversample: u32,
     pub(crate) started: u32,
     pub(crate) mem: Vec<f32>,
-    pub(crate) sinc_table: Vec<f32>,
+    // Shared between streams built from the same `ResamplerFilter`, and
+    // copied on write.
+    pub(crate) sinc_table: Arc<[f32]>,
     pub(crate) sinc_table_length: u32,
     pub(crate) resampler_ptr: ResamplerBasicFunc,
 
@@ -28,13 +32,17 @@ pub(crate) struct ResamplerState {
     pub(crate) last_sample: u32,
     pub(crate) samp_frac_num: u32,
     pub(crate) magic_samples: u32,
+
+    // Index into `QUALITY_MAPPING`.
+    pub(crate) quality: usize,
+    pub(crate) window: Window,
 }
 
 impl Default for ResamplerState {
     fn default() -> Self {
         Self {
             started: 0,
-            sinc_table: Vec::new(),
+            sinc_table: Arc::from(Vec::new()),
             sinc_table_length: 0,
             mem: Vec::new(),
             frac_advance: 0,
@@ -48,6 +56,8 @@ impl Default for ResamplerState {
             last_sample: 0,
             magic_samples: 0,
             samp_frac_num: 0,
+            quality: DEFAULT_QUALITY,
+            window: Window::default(),
         }
     }
 }
@@ -138,27 +148,21 @@ macro_rules! algo {
 }
 
 impl ResamplerState {
-    /* * Resample a float array. The input and output buffers must *not* overlap.
-     * @param st Resampler state
-     * @param in Input buffer
-     * @param in_len number of input samples in the input buffer. Returns the
-     * number of samples processed
-     * @param out Output buffer
-     * @param out_len Size of the output buffer. Returns the number of samples written
-     */
+    /// Resample `in_0` into `out`, returning the number of samples consumed
+    /// and produced.
     pub(crate) fn process_float(
         &mut self,
         mut in_0: &[f32],
-        in_len: &mut u32,
         mut out: &mut [f32],
-        out_len: &mut u32,
         den: u32,
-    ) {
+    ) -> (usize, usize) {
         if in_0.is_empty() {
             panic!("Empty slice is not allowed");
         }
-        let mut ilen = *in_len;
-        let mut olen = *out_len;
+        let in_len = in_0.len() as u32;
+        let out_len = out.len() as u32;
+        let mut ilen = in_len;
+        let mut olen = out_len;
         let filt_offs = (self.filt_len - 1) as usize;
         let mem_idx = filt_offs;
         let xlen = self.mem_alloc_size - self.filt_len - 1;
@@ -190,12 +194,11 @@ impl ResamplerState {
                 in_0 = &in_0[ichunk as usize..][..];
             }
         }
-        *in_len -= ilen;
-        *out_len -= olen;
         let resampler = self.resampler_ptr.unwrap();
         if resampler as usize == resampler_basic_zero as usize {
             panic!("alloc failed");
         }
+        ((in_len - ilen) as usize, (out_len - olen) as usize)
     }
 
     /* * Make sure that the first samples to go out of the resamplers don't have
@@ -225,8 +228,9 @@ impl ResamplerState {
 
     #[inline]
     fn num_den(&mut self, num: u32, den: u32) {
-        self.cutoff =
-            QUALITY_MAPPING.downsample_bandwidth * den as f32 / num as f32;
+        self.cutoff = QUALITY_MAPPING[self.quality].downsample_bandwidth
+            * den as f32
+            / num as f32;
         let pass = self.filt_len;
         self.filt_len = _muldiv(pass, num, den);
         self.filt_len = ((self.filt_len - 1) & (!7)) + 8;
@@ -241,14 +245,19 @@ impl ResamplerState {
 
     #[inline]
     fn use_direct(&mut self, den: u32) {
-        let iter_chunk = self.sinc_table.chunks_mut(self.filt_len as usize);
+        let cutoff = self.cutoff;
+        let filt_len = self.filt_len;
+        let window = self.window;
+        let sinc_table = Arc::get_mut(&mut self.sinc_table).unwrap();
+        let iter_chunk = sinc_table.chunks_mut(filt_len as usize);
         for (i, chunk) in iter_chunk.enumerate() {
             for (j, elem) in chunk.iter_mut().enumerate() {
                 *elem = sinc(
-                    self.cutoff,
-                    (j as f32 - self.filt_len as f32 / 2.0 + 1.0)
+                    cutoff,
+                    (j as f32 - filt_len as f32 / 2.0 + 1.0)
                         - (i as f32) / den as f32,
-                    self.filt_len as i32,
+                    filt_len as i32,
+                    window,
                 );
             }
         }
@@ -260,7 +269,9 @@ impl ResamplerState {
         let cutoff = self.cutoff;
         let oversample = self.oversample;
         let filt_len = self.filt_len;
-        self.sinc_table
+        let window = self.window;
+        Arc::get_mut(&mut self.sinc_table)
+            .unwrap()
             .iter_mut()
             .enumerate()
             .take((oversample * filt_len + 8) as usize)
@@ -270,6 +281,7 @@ impl ResamplerState {
                     (i as i32 - 4) as f32 / oversample as f32
                         - filt_len as f32 / 2.0,
                     filt_len as i32,
+                    window,
                 )
             });
         self.resampler_ptr = Some(resampler_basic_interpolate);
@@ -311,12 +323,13 @@ impl ResamplerState {
         let old_alloc_size = self.mem_alloc_size as usize;
         self.int_advance = num / den;
         self.frac_advance = num % den;
-        self.oversample = QUALITY_MAPPING.oversample as u32;
-        self.filt_len = QUALITY_MAPPING.base_length as u32;
+        let mapping = QUALITY_MAPPING[self.quality];
+        self.oversample = mapping.oversample as u32;
+        self.filt_len = mapping.base_length as u32;
         if num > den {
             self.num_den(num, den);
         } else {
-            self.cutoff = QUALITY_MAPPING.upsample_bandwidth;
+            self.cutoff = mapping.upsample_bandwidth;
         }
 
         let use_direct = self.filt_len * den
@@ -330,8 +343,12 @@ impl ResamplerState {
             self.filt_len * den
         };
 
-        if self.sinc_table_length < min_sinc_table_length {
-            self.sinc_table = vec![0.0; min_sinc_table_length as usize];
+        // Don't overwrite a table shared with other streams.
+        if self.sinc_table_length < min_sinc_table_length
+            || Arc::get_mut(&mut self.sinc_table).is_none()
+        {
+            self.sinc_table =
+                Arc::from(vec![0.0; min_sinc_table_length as usize]);
             self.sinc_table_length = min_sinc_table_length;
         }
 
@@ -352,15 +369,14 @@ impl ResamplerState {
         if self.started == 0 {
             let dim = self.mem_alloc_size as usize;
             self.mem = vec![0.0; dim];
+            // skip zeros (only at the start, so history isn't lost).
+            self.skip_zeros();
         } else if self.filt_len > old_length {
             self.chunks_iterator(old_length, old_alloc_size, 0);
             self.chunks_iterator(old_length, self.mem_alloc_size as usize, 1);
         } else if self.filt_len < old_length {
             self.chunks_iterator(old_length, self.mem_alloc_size as usize, 2);
         }
-
-        // skip zeros.
-        self.skip_zeros();
     }
 }
 
@@ -497,10 +513,29 @@ fn resampler_basic_interpolate(
     out_sample as i32
 }
 
-static QUALITY_MAPPING: QualityMapping =
-    QualityMapping::new(160, 16, 0.96, 0.96);
-
-fn sinc(cutoff: f32, x: f32, n: i32) -> f32 {
+/// Filter settings for each quality level, from 0 (fastest) to 10 (best).
+static QUALITY_MAPPING: [QualityMapping; MAX_QUALITY + 1] = [
+    QualityMapping::new(8, 4, 0.830, 0.860),
+    QualityMapping::new(16, 4, 0.850, 0.880),
+    QualityMapping::new(32, 4, 0.882, 0.910),
+    QualityMapping::new(48, 8, 0.895, 0.917),
+    QualityMapping::new(64, 8, 0.921, 0.940),
+    QualityMapping::new(80, 16, 0.922, 0.940),
+    QualityMapping::new(96, 16, 0.940, 0.945),
+    QualityMapping::new(128, 16, 0.950, 0.950),
+    QualityMapping::new(160, 16, 0.960, 0.960),
+    QualityMapping::new(192, 32, 0.968, 0.968),
+    QualityMapping::new(256, 32, 0.975, 0.975),
+];
+
+/// Quality level used unless changed.
+pub(crate) const DEFAULT_QUALITY: usize = 8;
+
+/// Highest supported quality level.
+pub(crate) const MAX_QUALITY: usize = 10;
+
+/// Windowed sinc with `n` taps, evaluated `x` samples from its center.
+pub(crate) fn sinc(cutoff: f32, x: f32, n: i32, window: Window) -> f32 {
     let xx = f64::from(x * cutoff);
     let x_abs = f64::from(x).abs();
     let n_64 = f64::from(n);
@@ -512,12 +547,12 @@ fn sinc(cutoff: f32, x: f32, n: i32) -> f32 {
     } else {
         let first_factor = cutoff_64 * (PI * xx).sin() / (PI * xx);
         let second_factor =
-            compute_func((2.0 * f64::from(x) / n_64).abs() as f32);
+            compute_func((2.0 * f64::from(x) / n_64).abs() as f32, window);
         (first_factor * second_factor) as f32
     }
 }
 
-fn compute_func(x: f32) -> f64 {
+fn compute_func(x: f32, window: Window) -> f64 {
     let mut interp: [f64; 4] = [0.0; 4];
     let y = x * super::WINDOW_FN_OVERSAMPLE as f32;
     let ind = y.floor() as usize;
@@ -531,7 +566,7 @@ fn compute_func(x: f32) -> f64 {
 
     interp
         .iter()
-        .zip(super::WINDOW_FN_KAISER_TABLE.iter().skip(ind))
+        .zip(window.table().iter().skip(ind))
         .map(|(&x, &y)| x * y)
         .sum()
 }
@@ -592,9 +627,11 @@ fn speex_resampler_process_native(
 ) {
     let n: usize = st.filt_len as usize;
     st.started = 1;
-    let mem = &st.mem.clone();
+    // Move the filter memory out while resampling from it, rather than
+    // cloning it (the resampler functions don't use `st.mem`).
+    let mut mem = mem::take(&mut st.mem);
     let out_sample: i32 = st.resampler_ptr.expect("non-null function pointer")(
-        st, mem, in_len, out, out_len, den,
+        st, &mem, in_len, out, out_len, den,
     );
     if st.last_sample < *in_len {
         *in_len = st.last_sample as u32;
@@ -603,7 +640,8 @@ fn speex_resampler_process_native(
     st.last_sample -= *in_len;
     let ilen = *in_len as usize;
 
-    st.mem[0..(n - 1)].copy_from_slice(&mem[ilen..(ilen + n - 1)]);
+    mem.copy_within(ilen..(ilen + n - 1), 0);
+    st.mem = mem;
 }
 
 fn speex_resampler_magic(
@@ -623,13 +661,9 @@ fn speex_resampler_magic(
     );
     st.magic_samples -= tmp_in_len;
     if st.magic_samples != 0 {
-        let mem = &st.mem[mem_idx - 1 + tmp_in_len as usize..].to_vec();
-        st.mem
-            .iter_mut()
-            .skip(mem_idx - 1)
-            .zip(mem.iter())
-            .take(st.magic_samples as usize)
-            .for_each(|(x, &y)| *x = y);
+        let start = mem_idx - 1 + tmp_in_len as usize;
+        let len = (st.magic_samples as usize).min(st.mem.len() - start);
+        st.mem.copy_within(start..start + len, mem_idx - 1);
     }
     let value: &mut [f32] = mem::take(out);
     *out = &mut value[(out_len as u32) as usize..];